@@ -0,0 +1,6 @@
+mod file;
+mod image;
+mod message;
+pub use file::*;
+pub use image::*;
+pub use message::*;
@@ -0,0 +1,207 @@
+use globset::GlobBuilder;
+use ignore::WalkBuilder;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 单个文件的大小上限, 超过时跳过
+pub const MAX_FILE_BYTES: u64 = 256 * 1024;
+/// 所有文件的总大小上限, 超过时报错
+pub const MAX_TOTAL_BYTES: usize = 1024 * 1024;
+
+/// 读取到的文本文件
+#[derive(Debug)]
+pub struct FileAttachment {
+    pub path: PathBuf,
+    pub content: String,
+}
+
+/// 文件被跳过的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    Binary,
+    TooLarge,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SkipReason::Binary => write!(f, "binary file"),
+            SkipReason::TooLarge => write!(f, "larger than {} KiB", MAX_FILE_BYTES / 1024),
+        }
+    }
+}
+
+/// `-f` 参数展开后的文件
+#[derive(Debug, Default)]
+pub struct FileAttachments {
+    pub files: Vec<FileAttachment>,
+    pub skipped: Vec<(PathBuf, SkipReason)>,
+}
+
+impl FileAttachments {
+    /// 展开文件路径、目录或 glob 并读取文件, 目录和 glob 遵循 `.gitignore` 并跳过隐藏文件
+    ///
+    /// 某个参数没有匹配到任何文件, 或总大小超过 [`MAX_TOTAL_BYTES`] 时返回错误。
+    pub fn load(patterns: &[String]) -> io::Result<Self> {
+        let mut attachments = Self::default();
+        let mut seen = HashSet::new();
+        let mut total = 0;
+        for pattern in patterns {
+            let paths = expand(pattern)?;
+            if paths.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no files match '{}'", pattern),
+                ));
+            }
+            for path in paths.into_iter().filter(|path| seen.insert(path.clone())) {
+                match read_text(&path)? {
+                    Ok(content) => {
+                        total += content.len();
+                        if total > MAX_TOTAL_BYTES {
+                            return Err(io::Error::other(format!(
+                                "attached files exceed {} KiB in total",
+                                MAX_TOTAL_BYTES / 1024
+                            )));
+                        }
+                        attachments.files.push(FileAttachment { path, content });
+                    }
+                    Err(reason) => attachments.skipped.push((path, reason)),
+                }
+            }
+        }
+        Ok(attachments)
+    }
+
+    /// 格式化为消息内容, 每个文件为路径加代码块
+    pub fn to_message(&self) -> String {
+        self.files.iter().map(format_file).collect()
+    }
+}
+
+fn format_file(file: &FileAttachment) -> String {
+    // 文件内容本身包含代码块时加长围栏
+    let mut fence = "```".to_string();
+    while file.content.contains(&fence) {
+        fence.push('`');
+    }
+    let language = file.path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+    format!(
+        "File: {}\n{}{}\n{}\n{}\n\n",
+        file.path.display(),
+        fence,
+        language,
+        file.content.trim_end_matches('\n'),
+        fence
+    )
+}
+
+/// 展开单个参数: 文件原样返回, 目录和 glob 遍历得到排序后的文件列表
+fn expand(pattern: &str) -> io::Result<Vec<PathBuf>> {
+    let path = Path::new(pattern);
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    if path.is_dir() {
+        return Ok(walk(path).collect());
+    }
+    if !pattern.contains(['*', '?', '[', '{']) {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("'{}' does not exist", pattern),
+        ));
+    }
+    let matcher = GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?
+        .compile_matcher();
+    // 从 glob 之前的目录开始遍历
+    let base: PathBuf = path
+        .components()
+        .take_while(|component| !component.as_os_str().to_string_lossy().contains(['*', '?', '[', '{']))
+        .collect();
+    let root = if base.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        &base
+    };
+    Ok(walk(root).filter(|path| matcher.is_match(path)).collect())
+}
+
+/// 遍历目录下的文件, 从当前目录开始时去掉路径的 `./` 前缀
+fn walk(root: &Path) -> impl Iterator<Item = PathBuf> {
+    WalkBuilder::new(root)
+        // 不在 git 仓库中时也遵循 .gitignore
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|file_type| file_type.is_file()))
+        .map(|entry| match entry.path().strip_prefix(".") {
+            Ok(path) => path.to_path_buf(),
+            Err(_) => entry.into_path(),
+        })
+}
+
+/// 读取文本文件, 过大、包含 NUL 字节或不是 UTF-8 的文件被跳过
+fn read_text(path: &Path) -> io::Result<Result<String, SkipReason>> {
+    if fs::metadata(path)?.len() > MAX_FILE_BYTES {
+        return Ok(Err(SkipReason::TooLarge));
+    }
+    let bytes = fs::read(path)?;
+    if bytes.contains(&0) {
+        return Ok(Err(SkipReason::Binary));
+    }
+    Ok(String::from_utf8(bytes).map_err(|_| SkipReason::Binary))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_directory_and_glob() {
+        let dir = std::env::temp_dir().join(format!("aichat-attach-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src/nested")).unwrap();
+        fs::write(dir.join(".gitignore"), "ignored.rs\n").unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.join("src/nested/lib.rs"), "```\n").unwrap();
+        fs::write(dir.join("src/ignored.rs"), "").unwrap();
+        fs::write(dir.join("src/data.bin"), [0u8, 1, 2]).unwrap();
+
+        let src = dir.join("src").to_string_lossy().into_owned();
+        let attachments = FileAttachments::load(std::slice::from_ref(&src)).unwrap();
+        let paths: Vec<_> = attachments.files.iter().map(|file| file.path.clone()).collect();
+        assert_eq!(paths, vec![dir.join("src/main.rs"), dir.join("src/nested/lib.rs")]);
+        assert_eq!(
+            attachments.skipped,
+            vec![(dir.join("src/data.bin"), SkipReason::Binary)]
+        );
+
+        // `*` 不跨目录, 重复的文件只读取一次
+        let glob = format!("{}/*.rs", src);
+        let attachments = FileAttachments::load(&[glob, format!("{}/main.rs", src)]).unwrap();
+        assert_eq!(attachments.files.len(), 1);
+        assert!(
+            attachments
+                .to_message()
+                .ends_with("main.rs\n```rs\nfn main() {}\n```\n\n")
+        );
+
+        assert!(FileAttachments::load(&[format!("{}/*.py", src)]).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_format_file_with_fence() {
+        let file = FileAttachment {
+            path: PathBuf::from("README.md"),
+            content: "```sh\nls\n```\n".to_string(),
+        };
+        assert_eq!(format_file(&file), "File: README.md\n````md\n```sh\nls\n```\n````\n\n");
+    }
+}
@@ -0,0 +1,55 @@
+use crate::chat::ChatImage;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// 单张图片的大小上限
+pub const MAX_IMAGE_BYTES: u64 = 20 * 1024 * 1024;
+
+/// 读取 PNG、JPEG 或 WebP 图片并 base64 编码, 格式按文件头判断而不是扩展名
+pub fn load_image(path: &Path) -> io::Result<ChatImage> {
+    if fs::metadata(path)?.len() > MAX_IMAGE_BYTES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("larger than {} MiB", MAX_IMAGE_BYTES / 1024 / 1024),
+        ));
+    }
+    let bytes = fs::read(path)?;
+    let mime_type = image_mime_type(&bytes).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "unsupported image format, expected PNG, JPEG or WebP",
+        )
+    })?;
+    Ok(ChatImage {
+        mime_type: mime_type.to_string(),
+        data: STANDARD.encode(bytes),
+    })
+}
+
+fn image_mime_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_mime_type() {
+        assert_eq!(image_mime_type(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), Some("image/png"));
+        assert_eq!(image_mime_type(&[0xFF, 0xD8, 0xFF, 0xE0]), Some("image/jpeg"));
+        assert_eq!(image_mime_type(b"RIFF\x24\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(image_mime_type(b"GIF89a"), None);
+        assert_eq!(image_mime_type(b"RIFF"), None);
+    }
+}
@@ -0,0 +1,20 @@
+use crate::chat::{ChatImage, ChatMessage};
+
+/// 附加到用户消息的文件内容和图片
+#[derive(Debug, Clone, Default)]
+pub struct MessageAttachments {
+    /// 格式化后的文件内容, 放在输入之前
+    pub text: String,
+    pub images: Vec<ChatImage>,
+}
+
+impl MessageAttachments {
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty() && self.images.is_empty()
+    }
+
+    /// 带附件的用户消息
+    pub fn user_message(&self, input: &str) -> ChatMessage {
+        ChatMessage::user(format!("{}{}", self.text, input)).with_images(self.images.clone())
+    }
+}
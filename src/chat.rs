@@ -1,149 +1,340 @@
-use crate::cli::response_render::{RenderConfig, ResponseRenderer};
-use crate::utils::StringUtils;
+mod anthropic;
+mod gemini;
+mod ollama;
+mod openai;
+mod provider;
+mod responses;
+
+use crate::cli::response_render::{RenderConfig, RenderMessage, ResponseRenderer};
 use crate::{
-    config::{ModelConfig, PromptConfig},
-    log_debug,
-};
-use async_openai::{
-    Client,
-    config::OpenAIConfig,
-    error::OpenAIError,
-    types::{
-        ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs, CreateChatCompletionRequest,
-        CreateChatCompletionRequestArgs,
-    },
+    config::{BudgetAction, Config, ConfigManager, ModelConfig, PromptConfig},
+    ledger::{BudgetExceeded, LedgerEntry, LedgerManager, check_budget},
+    log_debug, log_warn,
 };
+use chrono::Local;
+use crossterm::style::Stylize;
 use futures::StreamExt;
+pub use ollama::{DEFAULT_OLLAMA_BASE_URL, list_models as list_ollama_models};
+use provider::{ChatDelta, ChatProvider, ChatRequest, ProviderError, create_provider};
+pub use provider::{FinishReason, ResponseFormat, ResponseFormatRejected};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::Sender;
+
+/// 对话消息的角色(system prompt 由 PromptConfig 单独提供)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatRole {
+    User,
+    Assistant,
+}
+
+/// 一条对话消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: ChatRole,
+    pub content: String,
+    /// 用户消息附带的图片
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<ChatImage>,
+}
+
+impl ChatMessage {
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: ChatRole::User,
+            content: content.into(),
+            images: Vec::new(),
+        }
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self {
+            role: ChatRole::Assistant,
+            content: content.into(),
+            images: Vec::new(),
+        }
+    }
 
+    pub fn with_images(mut self, images: Vec<ChatImage>) -> Self {
+        self.images = images;
+        self
+    }
+}
+
+/// base64 编码的图片
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ChatImage {
+    /// 如 `image/png`
+    pub mime_type: String,
+    pub data: String,
+}
+
+impl ChatImage {
+    /// `data:<mime>;base64,<data>` 形式的 URL
+    pub fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.mime_type, self.data)
+    }
+}
+
+/// token 用量
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub total_tokens: u32,
+}
+
+/// 从 provider 的增量事件中汇总的回复
+#[derive(Debug, Default)]
+struct ProviderReply {
+    content: String,
+    usage: Option<TokenUsage>,
+    time_to_first_token: Option<Duration>,
+    finish_reason: Option<FinishReason>,
+}
+
+/// 一次请求的结果
+#[derive(Debug, Clone)]
+pub struct ChatResponse {
+    /// 完整的 assistant 回复内容
+    pub content: String,
+    pub usage: Option<TokenUsage>,
+    /// 收到首个 token 的耗时(仅流式)
+    pub time_to_first_token: Option<Duration>,
+    /// 收到完整响应的耗时
+    pub response_time: Duration,
+    /// 估算费用(需要在模型配置中设置价格)
+    pub cost: Option<f64>,
+    pub finish_reason: Option<FinishReason>,
+}
+
+impl ChatResponse {
+    /// 生成速度(token/秒), 流式时不计首 token 之前的等待时间
+    pub fn tokens_per_second(&self) -> Option<f64> {
+        let usage = self.usage?;
+        let generation_time = self.response_time - self.time_to_first_token.unwrap_or_default();
+        if usage.completion_tokens == 0 || generation_time.is_zero() {
+            return None;
+        }
+        Some(usage.completion_tokens as f64 / generation_time.as_secs_f64())
+    }
+}
+
+/// 发送对话请求并渲染响应, 结果会记录到用量账本
+///
+/// `ignore_budget` 为 true 时跳过预算检查; `quiet` 为 true 时不渲染回复, 由调用方自行处理;
+/// `response_format` 指定结构化输出的格式
+#[allow(clippy::too_many_arguments)]
 pub async fn completion(
-    input: &str,
+    messages: &[ChatMessage],
+    model_config_name: String,
+    model_config: &ModelConfig,
+    prompt_config_name: String,
+    prompt_config: &PromptConfig,
+    runtime_config: &Config,
+    ignore_budget: bool,
+    quiet: bool,
+    response_format: Option<&ResponseFormat>,
+) -> Result<ChatResponse, Box<dyn std::error::Error>> {
+    if !ignore_budget {
+        check_budgets(&model_config_name, model_config, runtime_config)?;
+    }
+
+    let start_time = Instant::now();
+    let result = send_completion(
+        messages,
+        model_config_name.clone(),
+        model_config,
+        prompt_config_name,
+        prompt_config,
+        runtime_config.pure,
+        runtime_config.disable_stream,
+        runtime_config.hide_reasoning,
+        quiet,
+        runtime_config.verbose,
+        response_format,
+    )
+    .await;
+    record_usage(&model_config_name, model_config, &result, start_time.elapsed());
+    result
+}
+
+/// 检查全局和模型的用量预算
+///
+/// 超出预算时按配置打印警告, 或返回 [`BudgetExceeded`] 拒绝发送。
+fn check_budgets(
+    model_config_name: &str,
+    model_config: &ModelConfig,
+    runtime_config: &Config,
+) -> Result<(), BudgetExceeded> {
+    let mut budgets = Vec::new();
+    if let Some(budget) = &runtime_config.budget {
+        budgets.push(("global".to_string(), budget, None));
+    }
+    if let Some(budget) = &model_config.budget {
+        budgets.push((
+            format!("model '{}'", model_config_name),
+            budget,
+            Some(model_config_name),
+        ));
+    }
+    if budgets.is_empty() {
+        return Ok(());
+    }
+
+    let entries = match ConfigManager::get_config_dir()
+        .and_then(|dir| LedgerManager::new(&dir))
+        .and_then(|ledger| ledger.load())
+    {
+        Ok(entries) => entries,
+        Err(e) => {
+            log_warn!("Failed to load usage ledger, skip budget check: {}", e);
+            return Ok(());
+        }
+    };
+
+    let now = Local::now();
+    let mut exceeded = Vec::new();
+    for (scope, budget, model_filter) in budgets {
+        for violation in check_budget(&entries, now, &scope, budget, model_filter) {
+            match budget.action.unwrap_or_default() {
+                BudgetAction::Warn => eprintln!("{}", format!("⚠ {}", violation).yellow()),
+                BudgetAction::Block => exceeded.push(violation),
+            }
+        }
+    }
+    if exceeded.is_empty() {
+        Ok(())
+    } else {
+        Err(BudgetExceeded { violations: exceeded })
+    }
+}
+
+/// 记录到用量账本, 写入失败只打印警告, 不影响对话
+fn record_usage(
+    model_config_name: &str,
+    model_config: &ModelConfig,
+    result: &Result<ChatResponse, Box<dyn std::error::Error>>,
+    latency: Duration,
+) {
+    let response = result.as_ref().ok();
+    let usage = response.and_then(|r| r.usage).unwrap_or_default();
+    let entry = LedgerEntry {
+        timestamp: Local::now(),
+        model_config_name: model_config_name.to_string(),
+        model_name: model_config.model_name.clone().unwrap_or_default(),
+        prompt_tokens: usage.prompt_tokens,
+        completion_tokens: usage.completion_tokens,
+        total_tokens: usage.total_tokens,
+        cost: response.and_then(|r| r.cost),
+        latency_ms: latency.as_millis() as u64,
+        success: result.is_ok(),
+    };
+    let recorded = ConfigManager::get_config_dir()
+        .and_then(|dir| LedgerManager::new(&dir))
+        .and_then(|ledger| ledger.append(&entry));
+    if let Err(e) = recorded {
+        log_warn!("Failed to record usage: {}", e);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn send_completion(
+    messages: &[ChatMessage],
     model_config_name: String,
     model_config: &ModelConfig,
     prompt_config_name: String,
     prompt_config: &PromptConfig,
     pure: bool,
     disable_stream: bool,
+    hide_reasoning: bool,
+    quiet: bool,
     verbose: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
+    response_format: Option<&ResponseFormat>,
+) -> Result<ChatResponse, Box<dyn std::error::Error>> {
     let _ = verbose;
-    let client = create_client(&model_config);
     let model_name = model_config.model_name.as_ref().unwrap();
+    // 结构化输出在完整回复后才校验, 不需要流式; 非流式请求的错误中也带有接口返回的原因
+    let disable_stream = disable_stream || response_format.is_some();
     // 创建渲染器配置
     let config = RenderConfig {
         pure,
-        model_config_name: model_config_name,
+        model_config_name,
         model_name: model_name.to_string(),
         prompt_config_name,
         type_speed: 30, // 50字/秒
-        disable_stream: disable_stream,
+        disable_stream,
+        hide_reasoning,
+        quiet,
     };
-    let mut errors = Vec::<OpenAIError>::new();
+    let start_time = Instant::now();
     let renderer = ResponseRenderer::new();
     let (message_tx, renderer_handler) = renderer.start_render(config);
 
-    if disable_stream {
-        log_debug!("Start send chat request.");
-        let response = client
-            .chat()
-            .create(create_request(input, &prompt_config, &model_config))
-            .await?;
-        log_debug!("Received chat response.");
-
-        if let Some(choice) = response.choices.first() {
-            message_tx
-                .send(choice.message.content.clone().unwrap_or(String::from("null")))
-                .await?;
-            // if let Err(err) = message_tx
-            //     .send(choice.message.content.clone().unwrap_or(String::from("null")))
-            //     .await
-            // {
-            //     eprintln!("send message failed: {}", err);
-            // };
-        }
-    } else {
-        let mut stream = client
-            .chat()
-            .create_stream(create_request(input, &prompt_config, &model_config))
-            .await?;
-
-        log_debug!("Start receive stream message.");
-        while let Some(result) = stream.next().await {
-            match result {
-                Ok(response) => {
-                    for chat_choice in response.choices.iter() {
-                        if let Some(content) = &chat_choice.delta.content {
-                            if let Err(_) = message_tx.send(content.clone()).await {};
-                        }
-                    }
-                }
-                Err(e) => {
-                    errors.push(e);
-                }
-            }
-        }
-        log_debug!("Exit receive stream message.");
-    }
+    let provider = create_provider(model_config).map_err(|e| e as Box<dyn std::error::Error>)?;
+    let request = ChatRequest {
+        messages,
+        system_prompt: &prompt_config.content,
+        stream: !disable_stream,
+        response_format,
+    };
+    let result = receive_reply(provider.as_ref(), request, &message_tx, start_time).await;
+    let response_time = start_time.elapsed();
     drop(message_tx);
     log_debug!("Drop Message Sender.");
     renderer_handler.await?;
     log_debug!("Response Render exit.");
-    if !errors.is_empty() {
-        for e in errors {
-            eprintln!("❌Error in sending openai-api request: {}", e);
-        }
-
-        return Err("failed to send request.".into());
-    }
-    if !pure {
-        renderer.render_tail_bar();
-    }
-    Ok(())
-}
-
-fn create_client(model_config: &ModelConfig) -> Client<OpenAIConfig> {
-    let env_api_key = std::env::var("OPENAI_API_KEY");
-    let final_api_key = match env_api_key {
-        Ok(val) => {
-            log_debug!("use env OPEN_API_KEY to override api-key.");
-            val
-        }
-        Err(_) => model_config.api_key.clone().unwrap_or(String::new()),
+    let reply = result.map_err(|e| e as Box<dyn std::error::Error>)?;
+    let response = ChatResponse {
+        content: reply.content,
+        usage: reply.usage,
+        time_to_first_token: reply.time_to_first_token,
+        response_time,
+        cost: reply.usage.as_ref().and_then(|u| model_config.estimate_cost(u)),
+        finish_reason: reply.finish_reason,
     };
-    log_debug!("final used api-key: {}", StringUtils::mask_sensitive(&final_api_key));
-    Client::with_config(
-        OpenAIConfig::default()
-            .with_api_key(final_api_key)
-            .with_api_base(model_config.base_url.as_ref().unwrap()),
-    )
+    if !pure && !quiet {
+        renderer.render_tail_bar(&response);
+    }
+    Ok(response)
 }
 
-fn create_request(
-    input: &str,
-    prompt_config: &PromptConfig,
-    model_config: &ModelConfig,
-) -> CreateChatCompletionRequest {
-    let mut builder = CreateChatCompletionRequestArgs::default();
-    builder.model(model_config.model_name.as_ref().unwrap());
-
-    match model_config.temperature {
-        Some(val) => {
-            builder.temperature(val);
+/// 接收 provider 的增量事件, 回复内容和推理过程写入渲染器通道
+async fn receive_reply(
+    provider: &dyn ChatProvider,
+    request: ChatRequest<'_>,
+    message_tx: &Sender<RenderMessage>,
+    start_time: Instant,
+) -> Result<ProviderReply, ProviderError> {
+    let stream = request.stream;
+    let mut deltas = provider.send(request).await?;
+    let mut reply = ProviderReply::default();
+    let mut errors = Vec::new();
+    log_debug!("Start receive reply deltas.");
+    while let Some(delta) = deltas.next().await {
+        match delta {
+            Ok(ChatDelta::Content(content)) => {
+                if stream {
+                    reply.time_to_first_token.get_or_insert_with(|| start_time.elapsed());
+                }
+                reply.content.push_str(&content);
+                let _ = message_tx.send(RenderMessage::Content(content)).await;
+            }
+            Ok(ChatDelta::Reasoning(reasoning)) => {
+                if stream {
+                    reply.time_to_first_token.get_or_insert_with(|| start_time.elapsed());
+                }
+                let _ = message_tx.send(RenderMessage::Reasoning(reasoning)).await;
+            }
+            Ok(ChatDelta::Usage(usage)) => reply.usage = Some(usage),
+            Ok(ChatDelta::Finish(reason)) => reply.finish_reason = Some(reason),
+            Err(e) => errors.push(format!("Error in sending chat request: {}", e)),
         }
-        None => {}
-    };
-    builder
-        .messages([
-            ChatCompletionRequestSystemMessageArgs::default()
-                .content(prompt_config.content.as_ref())
-                .build()
-                .unwrap()
-                .into(),
-            ChatCompletionRequestUserMessageArgs::default()
-                .content(input)
-                .build()
-                .unwrap()
-                .into(),
-        ])
-        .build()
-        .unwrap()
+    }
+    log_debug!("Exit receive reply deltas.");
+    if !errors.is_empty() {
+        return Err(errors.join("\n").into());
+    }
+    Ok(reply)
 }
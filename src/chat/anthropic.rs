@@ -0,0 +1,408 @@
+use super::provider::{
+    ChatDelta, ChatProvider, ChatRequest, DeltaStream, FinishReason, ProviderError, api_error_message, create_headers,
+    flatten_deltas, post_json, resolve_api_key,
+};
+use super::{ChatMessage, ChatRole, TokenUsage};
+use crate::config::ModelConfig;
+use crate::utils::{env_var, merge_json, to_json_value};
+use crate::{log_debug, log_trace};
+use eventsource_stream::{Event, Eventsource};
+use futures::future::{self, BoxFuture};
+use futures::{StreamExt, stream};
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 未配置 base_url 时使用的地址
+pub const DEFAULT_ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com/v1";
+/// Messages API 版本
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+/// Messages API 必须指定 max_tokens, 未配置时使用该值
+const DEFAULT_MAX_TOKENS: u32 = 4096;
+
+/// Anthropic Messages API
+pub struct AnthropicProvider {
+    client: reqwest::Client,
+    headers: HeaderMap,
+    model_config: ModelConfig,
+}
+
+impl AnthropicProvider {
+    pub fn new(model_config: &ModelConfig) -> Result<Self, ProviderError> {
+        let mut headers = create_headers(model_config)?;
+        for (name, value) in [
+            ("x-api-key", resolve_api_key(model_config, env_var)),
+            ("anthropic-version", ANTHROPIC_VERSION.to_string()),
+        ] {
+            if !headers.contains_key(name) {
+                headers.insert(name, value.parse()?);
+            }
+        }
+        Ok(Self {
+            client: reqwest::Client::new(),
+            headers,
+            model_config: model_config.clone(),
+        })
+    }
+}
+
+impl ChatProvider for AnthropicProvider {
+    fn send<'a>(&'a self, request: ChatRequest<'a>) -> BoxFuture<'a, Result<DeltaStream, ProviderError>> {
+        Box::pin(async move {
+            let base_url = self
+                .model_config
+                .base_url
+                .as_deref()
+                .unwrap_or(DEFAULT_ANTHROPIC_BASE_URL)
+                .trim_end_matches('/');
+            let body = create_request_body(
+                request.messages,
+                request.system_prompt,
+                &self.model_config,
+                request.stream,
+            )?;
+            let response = post_json(
+                &self.client,
+                format!("{}/messages", base_url),
+                self.headers.clone(),
+                &body,
+                "Anthropic",
+            )
+            .await?;
+
+            if !request.stream {
+                log_debug!("Received anthropic response.");
+                let response: MessageResponse = response.json().await?;
+                return Ok(stream::iter(response.deltas().into_iter().map(Ok)).boxed());
+            }
+            // message_start 中的输入 token 数需要保留到 message_delta 时一起上报
+            let stream = response
+                .bytes_stream()
+                .eventsource()
+                .scan(0, |input_tokens, event| {
+                    let deltas = match event {
+                        Ok(event) => event_deltas(input_tokens, &event),
+                        Err(e) => Err(e.into()),
+                    };
+                    future::ready(Some(stream::iter(flatten_deltas(deltas))))
+                })
+                .flatten();
+            Ok(stream.boxed())
+        })
+    }
+}
+
+/// 解析一个 SSE 事件
+fn event_deltas(input_tokens: &mut u32, event: &Event) -> Result<Vec<ChatDelta>, ProviderError> {
+    log_trace!("anthropic event {}: {}", event.event, event.data);
+    let mut deltas = Vec::new();
+    match event.event.as_str() {
+        "message_start" => {
+            let data: MessageStart = serde_json::from_str(&event.data)?;
+            *input_tokens = data.message.usage.input_tokens;
+        }
+        "content_block_delta" => {
+            let data: ContentBlockDelta = serde_json::from_str(&event.data)?;
+            deltas.extend(data.delta.thinking.map(ChatDelta::Reasoning));
+            deltas.extend(data.delta.text.map(ChatDelta::Content));
+        }
+        "message_delta" => {
+            let data: MessageDelta = serde_json::from_str(&event.data)?;
+            let usage = Usage {
+                input_tokens: *input_tokens,
+                output_tokens: data.usage.output_tokens,
+            };
+            deltas.push(ChatDelta::Usage(usage.into()));
+            deltas.extend(
+                data.delta
+                    .stop_reason
+                    .as_deref()
+                    .map(finish_reason)
+                    .map(ChatDelta::Finish),
+            );
+        }
+        "error" => return Err(format!("Anthropic API error: {}", api_error_message(&event.data)).into()),
+        _ => {}
+    }
+    Ok(deltas)
+}
+
+fn finish_reason(stop_reason: &str) -> FinishReason {
+    match stop_reason {
+        "end_turn" | "stop_sequence" => FinishReason::Stop,
+        "max_tokens" => FinishReason::Length,
+        "refusal" => FinishReason::ContentFilter,
+        other => FinishReason::Other(other.to_string()),
+    }
+}
+
+/// 生成 Messages API 请求体, system prompt 作为顶层字段
+fn create_request_body(
+    messages: &[ChatMessage],
+    system_prompt: &str,
+    model_config: &ModelConfig,
+    stream: bool,
+) -> Result<Value, serde_json::Error> {
+    let request = MessagesRequest {
+        model: model_config.model_name.as_ref().unwrap(),
+        system: system_prompt,
+        messages: messages.iter().map(Message::from).collect(),
+        max_tokens: model_config
+            .max_completion_tokens
+            .or(model_config.max_tokens)
+            .unwrap_or(DEFAULT_MAX_TOKENS),
+        temperature: model_config.temperature,
+        top_p: model_config.top_p,
+        stop_sequences: model_config.stop.as_deref(),
+        stream,
+    };
+    let mut body = to_json_value(&request)?;
+    if let Some(extra_body) = &model_config.extra_body {
+        merge_json(&mut body, extra_body);
+    }
+    log_trace!("anthropic request body: {}", body);
+    Ok(body)
+}
+
+#[derive(Debug, Serialize)]
+struct MessagesRequest<'a> {
+    model: &'a str,
+    system: &'a str,
+    messages: Vec<Message<'a>>,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<&'a [String]>,
+    stream: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Message<'a> {
+    role: ChatRole,
+    content: MessageContent<'a>,
+}
+
+/// 纯文本消息使用字符串, 带图片时使用内容块数组
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum MessageContent<'a> {
+    Text(&'a str),
+    Blocks(Vec<ContentBlockParam<'a>>),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlockParam<'a> {
+    Image { source: ImageSource<'a> },
+    Text { text: &'a str },
+}
+
+#[derive(Debug, Serialize)]
+struct ImageSource<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    media_type: &'a str,
+    data: &'a str,
+}
+
+impl<'a> From<&'a ChatMessage> for Message<'a> {
+    fn from(message: &'a ChatMessage) -> Self {
+        if message.images.is_empty() {
+            return Self {
+                role: message.role,
+                content: MessageContent::Text(&message.content),
+            };
+        }
+        // 图片放在文本之前
+        let mut blocks: Vec<_> = message
+            .images
+            .iter()
+            .map(|image| ContentBlockParam::Image {
+                source: ImageSource {
+                    kind: "base64",
+                    media_type: &image.mime_type,
+                    data: &image.data,
+                },
+            })
+            .collect();
+        blocks.push(ContentBlockParam::Text { text: &message.content });
+        Self {
+            role: message.role,
+            content: MessageContent::Blocks(blocks),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Usage {
+    #[serde(default)]
+    input_tokens: u32,
+    #[serde(default)]
+    output_tokens: u32,
+}
+
+impl From<Usage> for TokenUsage {
+    fn from(usage: Usage) -> Self {
+        Self {
+            prompt_tokens: usage.input_tokens,
+            completion_tokens: usage.output_tokens,
+            total_tokens: usage.input_tokens + usage.output_tokens,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ContentBlock {
+    text: Option<String>,
+    thinking: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageResponse {
+    content: Vec<ContentBlock>,
+    stop_reason: Option<String>,
+    usage: Usage,
+}
+
+impl MessageResponse {
+    fn deltas(self) -> Vec<ChatDelta> {
+        let thinking: String = self
+            .content
+            .iter()
+            .filter_map(|block| block.thinking.as_deref())
+            .collect();
+        let content: String = self.content.iter().filter_map(|block| block.text.as_deref()).collect();
+        let mut deltas = Vec::new();
+        if !thinking.is_empty() {
+            deltas.push(ChatDelta::Reasoning(thinking));
+        }
+        deltas.extend([ChatDelta::Content(content), ChatDelta::Usage(self.usage.into())]);
+        deltas.extend(self.stop_reason.as_deref().map(finish_reason).map(ChatDelta::Finish));
+        deltas
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageStart {
+    message: MessageStartBody,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageStartBody {
+    usage: Usage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContentBlockDelta {
+    delta: TextDelta,
+}
+
+/// `text_delta` 或 `thinking_delta`
+#[derive(Debug, Deserialize)]
+struct TextDelta {
+    text: Option<String>,
+    thinking: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageDelta {
+    delta: MessageDeltaBody,
+    usage: Usage,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageDeltaBody {
+    stop_reason: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Provider;
+    use serde_json::json;
+
+    #[test]
+    fn test_resolve_api_key() {
+        let env = |name: &str| match name {
+            "OPENAI_API_KEY" => Some("sk-openai-secret".to_string()),
+            "ANTHROPIC_API_KEY" => Some("ak-from-env".to_string()),
+            _ => None,
+        };
+        let model_config = ModelConfig {
+            provider: Some(Provider::Anthropic),
+            api_key: Some("ak-configured".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(resolve_api_key(&model_config, env), "ak-configured");
+        let model_config = ModelConfig {
+            api_key: None,
+            ..model_config
+        };
+        assert_eq!(resolve_api_key(&model_config, env), "ak-from-env");
+        let only_openai = |name: &str| (name == "OPENAI_API_KEY").then(|| "sk-openai-secret".to_string());
+        assert_eq!(resolve_api_key(&model_config, only_openai), "");
+    }
+
+    #[test]
+    fn test_create_request_body() {
+        let model_config = ModelConfig {
+            model_name: Some("claude-sonnet-4-5".to_string()),
+            temperature: Some(0.3),
+            stop: Some(vec!["END".to_string()]),
+            extra_body: Some(json!({"top_k": 20})),
+            ..Default::default()
+        };
+        let body = create_request_body(&[ChatMessage::user("hi")], "be concise", &model_config, true).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "model": "claude-sonnet-4-5",
+                "system": "be concise",
+                "messages": [{"role": "user", "content": "hi"}],
+                "max_tokens": DEFAULT_MAX_TOKENS,
+                "temperature": 0.3,
+                "stop_sequences": ["END"],
+                "stream": true,
+                "top_k": 20
+            })
+        );
+    }
+
+    #[test]
+    fn test_event_deltas() {
+        let event = |name: &str, data: Value| Event {
+            event: name.to_string(),
+            data: data.to_string(),
+            ..Default::default()
+        };
+        let mut input_tokens = 0;
+        let start = event("message_start", json!({"message": {"usage": {"input_tokens": 12}}}));
+        assert!(event_deltas(&mut input_tokens, &start).unwrap().is_empty());
+        let text = event(
+            "content_block_delta",
+            json!({"delta": {"type": "text_delta", "text": "hi"}}),
+        );
+        assert_eq!(
+            event_deltas(&mut input_tokens, &text).unwrap(),
+            vec![ChatDelta::Content("hi".to_string())]
+        );
+        let end = event(
+            "message_delta",
+            json!({"delta": {"stop_reason": "max_tokens"}, "usage": {"output_tokens": 7}}),
+        );
+        assert_eq!(
+            event_deltas(&mut input_tokens, &end).unwrap(),
+            vec![
+                ChatDelta::Usage(TokenUsage {
+                    prompt_tokens: 12,
+                    completion_tokens: 7,
+                    total_tokens: 19
+                }),
+                ChatDelta::Finish(FinishReason::Length)
+            ]
+        );
+    }
+}
@@ -0,0 +1,385 @@
+use super::provider::{
+    ChatDelta, ChatProvider, ChatRequest, DeltaStream, FinishReason, ProviderError, ResponseFormat, create_headers,
+    flatten_deltas, post_json, resolve_api_key,
+};
+use super::{ChatMessage, ChatRole, TokenUsage};
+use crate::config::ModelConfig;
+use crate::utils::{env_var, merge_json, to_json_value};
+use crate::{log_debug, log_trace};
+use eventsource_stream::Eventsource;
+use futures::future::BoxFuture;
+use futures::{StreamExt, stream};
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 未配置 base_url 时使用的地址
+pub const DEFAULT_GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+/// Google Gemini generateContent/streamGenerateContent
+pub struct GeminiProvider {
+    client: reqwest::Client,
+    headers: HeaderMap,
+    model_config: ModelConfig,
+}
+
+impl GeminiProvider {
+    pub fn new(model_config: &ModelConfig) -> Result<Self, ProviderError> {
+        let mut headers = create_headers(model_config)?;
+        if !headers.contains_key("x-goog-api-key") {
+            headers.insert("x-goog-api-key", resolve_api_key(model_config, env_var).parse()?);
+        }
+        Ok(Self {
+            client: reqwest::Client::new(),
+            headers,
+            model_config: model_config.clone(),
+        })
+    }
+}
+
+impl ChatProvider for GeminiProvider {
+    fn send<'a>(&'a self, request: ChatRequest<'a>) -> BoxFuture<'a, Result<DeltaStream, ProviderError>> {
+        Box::pin(async move {
+            let base_url = self
+                .model_config
+                .base_url
+                .as_deref()
+                .unwrap_or(DEFAULT_GEMINI_BASE_URL)
+                .trim_end_matches('/');
+            let model_name = self
+                .model_config
+                .model_name
+                .as_deref()
+                .ok_or("Gemini model name is not configured")?;
+            let url = if request.stream {
+                format!("{}/models/{}:streamGenerateContent?alt=sse", base_url, model_name)
+            } else {
+                format!("{}/models/{}:generateContent", base_url, model_name)
+            };
+            let body = create_request_body(
+                request.messages,
+                request.system_prompt,
+                &self.model_config,
+                request.response_format,
+            )?;
+            let response = post_json(&self.client, url, self.headers.clone(), &body, "Gemini").await?;
+
+            if !request.stream {
+                log_debug!("Received gemini response.");
+                let response: GenerateContentResponse = response.json().await?;
+                return Ok(stream::iter(response.deltas().into_iter().map(Ok)).boxed());
+            }
+            // 每个分片的格式与非流式响应相同, 用量为截至当前的累计值
+            let stream = response.bytes_stream().eventsource().flat_map(|event| {
+                let deltas = event.map_err(ProviderError::from).and_then(|event| {
+                    log_trace!("gemini event: {}", event.data);
+                    let chunk: GenerateContentResponse = serde_json::from_str(&event.data)?;
+                    Ok(chunk.deltas())
+                });
+                stream::iter(flatten_deltas(deltas))
+            });
+            Ok(stream.boxed())
+        })
+    }
+}
+
+/// 生成请求体, system prompt 放在 `systemInstruction`, 采样参数放在 `generationConfig`
+fn create_request_body<'a>(
+    messages: &'a [ChatMessage],
+    system_prompt: &'a str,
+    model_config: &'a ModelConfig,
+    response_format: Option<&'a ResponseFormat>,
+) -> Result<Value, serde_json::Error> {
+    let request = GenerateContentRequest {
+        system_instruction: Content {
+            role: None,
+            parts: vec![Part::text(system_prompt)],
+        },
+        contents: messages
+            .iter()
+            .map(|message| Content {
+                role: Some(match message.role {
+                    ChatRole::User => "user",
+                    ChatRole::Assistant => "model",
+                }),
+                // 图片放在文本之前
+                parts: message
+                    .images
+                    .iter()
+                    .map(|image| Part::InlineData {
+                        inline_data: Blob {
+                            mime_type: &image.mime_type,
+                            data: &image.data,
+                        },
+                    })
+                    .chain([Part::text(&message.content)])
+                    .collect(),
+            })
+            .collect(),
+        generation_config: GenerationConfig {
+            temperature: model_config.temperature,
+            top_p: model_config.top_p,
+            max_output_tokens: model_config.max_completion_tokens.or(model_config.max_tokens),
+            stop_sequences: model_config.stop.as_deref(),
+            candidate_count: model_config.n,
+            seed: model_config.seed,
+            presence_penalty: model_config.presence_penalty,
+            frequency_penalty: model_config.frequency_penalty,
+            response_mime_type: response_format.map(|_| "application/json"),
+            response_json_schema: match response_format {
+                Some(ResponseFormat::JsonSchema { schema, .. }) => Some(schema),
+                _ => None,
+            },
+        },
+    };
+    let mut body = to_json_value(&request)?;
+    if let Some(extra_body) = &model_config.extra_body {
+        merge_json(&mut body, extra_body);
+    }
+    log_trace!("gemini request body: {}", body);
+    Ok(body)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerateContentRequest<'a> {
+    system_instruction: Content<'a>,
+    contents: Vec<Content<'a>>,
+    generation_config: GenerationConfig<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct Content<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    role: Option<&'a str>,
+    parts: Vec<Part<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum Part<'a> {
+    Text {
+        text: &'a str,
+    },
+    InlineData {
+        #[serde(rename = "inlineData")]
+        inline_data: Blob<'a>,
+    },
+}
+
+impl<'a> Part<'a> {
+    fn text(text: &'a str) -> Self {
+        Self::Text { text }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Blob<'a> {
+    mime_type: &'a str,
+    data: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerationConfig<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_output_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<&'a [String]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    candidate_count: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frequency_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_mime_type: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_json_schema: Option<&'a Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerateContentResponse {
+    #[serde(default)]
+    candidates: Vec<Candidate>,
+    usage_metadata: Option<UsageMetadata>,
+}
+
+impl GenerateContentResponse {
+    /// 第一个候选的思考摘要、文本、结束原因和用量
+    fn deltas(self) -> Vec<ChatDelta> {
+        let mut deltas = Vec::new();
+        if let Some(candidate) = self.candidates.into_iter().find(|c| c.index.unwrap_or_default() == 0) {
+            let parts = candidate.content.iter().flat_map(|content| &content.parts);
+            let (thoughts, parts): (Vec<_>, Vec<_>) = parts.partition(|part| part.thought);
+            let thought: String = thoughts.iter().filter_map(|part| part.text.as_deref()).collect();
+            if !thought.is_empty() {
+                deltas.push(ChatDelta::Reasoning(thought));
+            }
+            let text: String = parts.iter().filter_map(|part| part.text.as_deref()).collect();
+            if !text.is_empty() {
+                deltas.push(ChatDelta::Content(text));
+            }
+            deltas.extend(
+                candidate
+                    .finish_reason
+                    .as_deref()
+                    .map(finish_reason)
+                    .map(ChatDelta::Finish),
+            );
+        }
+        deltas.extend(self.usage_metadata.map(|usage| ChatDelta::Usage(usage.into())));
+        deltas
+    }
+}
+
+fn finish_reason(reason: &str) -> FinishReason {
+    match reason {
+        "STOP" => FinishReason::Stop,
+        "MAX_TOKENS" => FinishReason::Length,
+        "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII" => FinishReason::ContentFilter,
+        other => FinishReason::Other(other.to_lowercase()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Candidate {
+    index: Option<u32>,
+    content: Option<ResponseContent>,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseContent {
+    #[serde(default)]
+    parts: Vec<ResponsePart>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponsePart {
+    text: Option<String>,
+    #[serde(default)]
+    thought: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageMetadata {
+    #[serde(default)]
+    prompt_token_count: u32,
+    #[serde(default)]
+    candidates_token_count: u32,
+    #[serde(default)]
+    thoughts_token_count: u32,
+}
+
+impl From<UsageMetadata> for TokenUsage {
+    fn from(usage: UsageMetadata) -> Self {
+        let completion_tokens = usage.candidates_token_count + usage.thoughts_token_count;
+        Self {
+            prompt_tokens: usage.prompt_token_count,
+            completion_tokens,
+            total_tokens: usage.prompt_token_count + completion_tokens,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Provider;
+    use serde_json::json;
+
+    #[test]
+    fn test_resolve_api_key() {
+        let model_config = ModelConfig {
+            provider: Some(Provider::Gemini),
+            api_key: None,
+            ..Default::default()
+        };
+        // 两个变量都存在时优先 GEMINI_API_KEY, 且从不使用 OPENAI_API_KEY
+        let env = |name: &str| match name {
+            "OPENAI_API_KEY" => Some("sk-openai-secret".to_string()),
+            "GEMINI_API_KEY" => Some("gk-gemini".to_string()),
+            "GOOGLE_API_KEY" => Some("gk-google".to_string()),
+            _ => None,
+        };
+        assert_eq!(resolve_api_key(&model_config, env), "gk-gemini");
+        let google_only = |name: &str| (name != "GEMINI_API_KEY").then(|| env(name)).flatten();
+        assert_eq!(resolve_api_key(&model_config, google_only), "gk-google");
+        let configured = ModelConfig {
+            api_key: Some("gk-configured".to_string()),
+            ..model_config
+        };
+        assert_eq!(resolve_api_key(&configured, env), "gk-configured");
+    }
+
+    #[test]
+    fn test_response_deltas() {
+        let response: GenerateContentResponse = serde_json::from_value(json!({
+            "candidates": [{
+                "index": 0,
+                "content": {"role": "model", "parts": [
+                    {"text": "planning", "thought": true},
+                    {"text": "hel"},
+                    {"text": "lo"}
+                ]},
+                "finishReason": "MAX_TOKENS"
+            }],
+            "usageMetadata": {"promptTokenCount": 8, "candidatesTokenCount": 6, "thoughtsTokenCount": 3}
+        }))
+        .unwrap();
+        assert_eq!(
+            response.deltas(),
+            vec![
+                ChatDelta::Reasoning("planning".to_string()),
+                ChatDelta::Content("hello".to_string()),
+                ChatDelta::Finish(FinishReason::Length),
+                ChatDelta::Usage(TokenUsage {
+                    prompt_tokens: 8,
+                    completion_tokens: 9,
+                    total_tokens: 17
+                })
+            ]
+        );
+        assert_eq!(finish_reason("SAFETY"), FinishReason::ContentFilter);
+        assert_eq!(finish_reason("OTHER"), FinishReason::Other("other".to_string()));
+    }
+
+    #[test]
+    fn test_create_request_body() {
+        let model_config = ModelConfig {
+            model_name: Some("gemini-2.5-flash".to_string()),
+            temperature: Some(0.3),
+            max_tokens: Some(512),
+            ..Default::default()
+        };
+        let messages = [
+            ChatMessage::user("hi"),
+            ChatMessage::assistant("hello"),
+            ChatMessage::user("bye"),
+        ];
+        let body = create_request_body(&messages, "be concise", &model_config, None).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "systemInstruction": {"parts": [{"text": "be concise"}]},
+                "contents": [
+                    {"role": "user", "parts": [{"text": "hi"}]},
+                    {"role": "model", "parts": [{"text": "hello"}]},
+                    {"role": "user", "parts": [{"text": "bye"}]}
+                ],
+                "generationConfig": {"temperature": 0.3, "maxOutputTokens": 512}
+            })
+        );
+    }
+}
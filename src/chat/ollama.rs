@@ -0,0 +1,276 @@
+use super::provider::{
+    ChatDelta, ChatProvider, ChatRequest, DeltaStream, FinishReason, ProviderError, ResponseFormat, api_error_message,
+    create_headers, flatten_deltas, post_json,
+};
+use super::{ChatMessage, ChatRole, TokenUsage};
+use crate::config::ModelConfig;
+use crate::utils::{merge_json, to_json_value};
+use crate::{log_debug, log_trace};
+use futures::future::{self, BoxFuture};
+use futures::{StreamExt, stream};
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Ollama 本地服务默认地址
+pub const DEFAULT_OLLAMA_BASE_URL: &str = "http://localhost:11434";
+
+/// Ollama 原生 `/api/chat` 接口
+pub struct OllamaProvider {
+    client: reqwest::Client,
+    headers: HeaderMap,
+    model_config: ModelConfig,
+}
+
+impl OllamaProvider {
+    pub fn new(model_config: &ModelConfig) -> Result<Self, ProviderError> {
+        Ok(Self {
+            client: reqwest::Client::new(),
+            headers: create_headers(model_config)?,
+            model_config: model_config.clone(),
+        })
+    }
+}
+
+impl ChatProvider for OllamaProvider {
+    fn send<'a>(&'a self, request: ChatRequest<'a>) -> BoxFuture<'a, Result<DeltaStream, ProviderError>> {
+        Box::pin(async move {
+            let url = format!("{}/api/chat", base_url(&self.model_config));
+            let body = create_request_body(
+                request.messages,
+                request.system_prompt,
+                &self.model_config,
+                request.stream,
+                request.response_format,
+            )?;
+            let response = post_json(&self.client, url, self.headers.clone(), &body, "Ollama").await?;
+
+            if !request.stream {
+                log_debug!("Received ollama response.");
+                let response: ChatResponse = response.json().await?;
+                return Ok(stream::iter(response.deltas().into_iter().map(Ok)).boxed());
+            }
+            // 流式响应为 NDJSON, 一行一个 JSON 对象, 网络分片可能截断在行中间
+            let stream = response
+                .bytes_stream()
+                .scan(Vec::new(), |buffer, chunk| {
+                    let deltas = chunk.map_err(ProviderError::from).and_then(|chunk| {
+                        buffer.extend_from_slice(&chunk);
+                        let mut deltas = Vec::new();
+                        while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                            let line: Vec<u8> = buffer.drain(..=pos).collect();
+                            deltas.extend(line_deltas(&line)?);
+                        }
+                        Ok(deltas)
+                    });
+                    future::ready(Some(stream::iter(flatten_deltas(deltas))))
+                })
+                .flatten();
+            Ok(stream.boxed())
+        })
+    }
+}
+
+/// 读取本地已下载的模型列表(`/api/tags`)
+pub async fn list_models(base_url: &str) -> Result<Vec<String>, ProviderError> {
+    let url = format!("{}/api/tags", base_url.trim_end_matches('/'));
+    let response = reqwest::get(url).await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Ollama API error {}: {}", status, api_error_message(&body)).into());
+    }
+    let tags: TagsResponse = response.json().await?;
+    Ok(tags.models.into_iter().map(|model| model.name).collect())
+}
+
+fn base_url(model_config: &ModelConfig) -> &str {
+    model_config
+        .base_url
+        .as_deref()
+        .unwrap_or(DEFAULT_OLLAMA_BASE_URL)
+        .trim_end_matches('/')
+}
+
+/// 解析 NDJSON 中的一行
+fn line_deltas(line: &[u8]) -> Result<Vec<ChatDelta>, ProviderError> {
+    let line = std::str::from_utf8(line)?.trim();
+    if line.is_empty() {
+        return Ok(Vec::new());
+    }
+    log_trace!("ollama line: {}", line);
+    let chunk: ChatResponse = serde_json::from_str(line)?;
+    if let Some(error) = chunk.error {
+        return Err(format!("Ollama API error: {}", error).into());
+    }
+    Ok(chunk.deltas())
+}
+
+/// 生成请求体, system prompt 作为第一条消息, 采样参数放在 `options`
+fn create_request_body(
+    messages: &[ChatMessage],
+    system_prompt: &str,
+    model_config: &ModelConfig,
+    stream: bool,
+    response_format: Option<&ResponseFormat>,
+) -> Result<Value, serde_json::Error> {
+    let mut request_messages = vec![Message {
+        role: "system",
+        content: system_prompt,
+        images: Vec::new(),
+    }];
+    request_messages.extend(messages.iter().map(|message| Message {
+        role: match message.role {
+            ChatRole::User => "user",
+            ChatRole::Assistant => "assistant",
+        },
+        content: &message.content,
+        images: message.images.iter().map(|image| image.data.as_str()).collect(),
+    }));
+    let request = ChatRequestBody {
+        model: model_config.model_name.as_ref().unwrap(),
+        messages: request_messages,
+        stream,
+        // `format` 可以是 `"json"` 或 JSON Schema
+        format: response_format.map(|format| match format {
+            ResponseFormat::JsonSchema { schema, .. } => schema.clone(),
+            ResponseFormat::JsonObject => Value::from("json"),
+        }),
+        options: Options {
+            temperature: model_config.temperature,
+            top_p: model_config.top_p,
+            num_predict: model_config.max_completion_tokens.or(model_config.max_tokens),
+            stop: model_config.stop.as_deref(),
+            seed: model_config.seed,
+            presence_penalty: model_config.presence_penalty,
+            frequency_penalty: model_config.frequency_penalty,
+        },
+    };
+    let mut body = to_json_value(&request)?;
+    if let Some(extra_body) = &model_config.extra_body {
+        merge_json(&mut body, extra_body);
+    }
+    log_trace!("ollama request body: {}", body);
+    Ok(body)
+}
+
+#[derive(Debug, Serialize)]
+struct ChatRequestBody<'a> {
+    model: &'a str,
+    messages: Vec<Message<'a>>,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<Value>,
+    options: Options<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct Message<'a> {
+    role: &'a str,
+    content: &'a str,
+    /// base64 编码的图片, 不带 data URL 前缀
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    images: Vec<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+struct Options<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_predict: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<&'a [String]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frequency_penalty: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    message: Option<ResponseMessage>,
+    #[serde(default)]
+    done: bool,
+    done_reason: Option<String>,
+    prompt_eval_count: Option<u32>,
+    eval_count: Option<u32>,
+    error: Option<String>,
+}
+
+impl ChatResponse {
+    /// 回复片段; 最后一行(`done: true`)带有结束原因和用量
+    fn deltas(self) -> Vec<ChatDelta> {
+        let mut deltas = Vec::new();
+        if let Some(message) = self.message {
+            deltas.extend(message.thinking.filter(|t| !t.is_empty()).map(ChatDelta::Reasoning));
+            if !message.content.is_empty() {
+                deltas.push(ChatDelta::Content(message.content));
+            }
+        }
+        if self.done {
+            let prompt_tokens = self.prompt_eval_count.unwrap_or_default();
+            let completion_tokens = self.eval_count.unwrap_or_default();
+            deltas.push(ChatDelta::Usage(TokenUsage {
+                prompt_tokens,
+                completion_tokens,
+                total_tokens: prompt_tokens + completion_tokens,
+            }));
+            deltas.extend(self.done_reason.map(|reason| {
+                ChatDelta::Finish(match reason.as_str() {
+                    "stop" => FinishReason::Stop,
+                    "length" => FinishReason::Length,
+                    _ => FinishReason::Other(reason),
+                })
+            }));
+        }
+        deltas
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseMessage {
+    #[serde(default)]
+    content: String,
+    /// 开启 `think` 时的思考过程
+    thinking: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TagsResponse {
+    models: Vec<TagsModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TagsModel {
+    name: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_deltas() {
+        let line = br#"{"message":{"role":"assistant","content":"hi"},"done":false}"#;
+        assert_eq!(line_deltas(line).unwrap(), vec![ChatDelta::Content("hi".to_string())]);
+        let done = br#"{"message":{"role":"assistant","content":""},"done":true,"done_reason":"length","prompt_eval_count":5,"eval_count":3}"#;
+        assert_eq!(
+            line_deltas(done).unwrap(),
+            vec![
+                ChatDelta::Usage(TokenUsage {
+                    prompt_tokens: 5,
+                    completion_tokens: 3,
+                    total_tokens: 8
+                }),
+                ChatDelta::Finish(FinishReason::Length)
+            ]
+        );
+        assert!(line_deltas(b"  \n").unwrap().is_empty());
+        assert!(line_deltas(br#"{"error":"model not found"}"#).is_err());
+    }
+}
@@ -0,0 +1,472 @@
+use super::provider::{
+    self, ChatDelta, ChatProvider, ChatRequest, DeltaStream, FinishReason, ProviderError, ResponseFormatRejected,
+    create_headers, flatten_deltas, mentions_response_format, post_json, resolve_api_key,
+};
+use super::{ChatMessage, ChatRole, TokenUsage, responses};
+use crate::config::{self, ModelConfig, OpenAIApi, Provider};
+use crate::utils::{env_var, merge_json, to_json_value};
+use crate::{log_debug, log_trace};
+use async_openai::{
+    Client,
+    config::{AzureConfig, Config, OpenAIConfig},
+    error::OpenAIError,
+    types::{
+        ChatCompletionRequestAssistantMessageArgs, ChatCompletionRequestMessage,
+        ChatCompletionRequestMessageContentPartImage, ChatCompletionRequestMessageContentPartText,
+        ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs,
+        ChatCompletionRequestUserMessageContent, ChatCompletionStreamOptions, CompletionUsage,
+        CreateChatCompletionRequest, CreateChatCompletionRequestArgs, ImageDetail, ImageUrl, ReasoningEffort,
+        ResponseFormat, ResponseFormatJsonSchema, Stop,
+    },
+};
+use eventsource_stream::Eventsource;
+use futures::future::BoxFuture;
+use futures::{StreamExt, stream};
+use reqwest::header::HeaderMap;
+use secrecy::SecretString;
+use serde::Deserialize;
+use serde_json::Value;
+
+impl From<&CompletionUsage> for TokenUsage {
+    fn from(usage: &CompletionUsage) -> Self {
+        Self {
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+            total_tokens: usage.total_tokens,
+        }
+    }
+}
+
+impl From<async_openai::types::FinishReason> for FinishReason {
+    fn from(reason: async_openai::types::FinishReason) -> Self {
+        use async_openai::types::FinishReason as OpenAIFinishReason;
+        match reason {
+            OpenAIFinishReason::Stop => FinishReason::Stop,
+            OpenAIFinishReason::Length => FinishReason::Length,
+            OpenAIFinishReason::ContentFilter => FinishReason::ContentFilter,
+            OpenAIFinishReason::ToolCalls => FinishReason::Other("tool_calls".to_string()),
+            OpenAIFinishReason::FunctionCall => FinishReason::Other("function_call".to_string()),
+        }
+    }
+}
+
+/// OpenAI 兼容的 `/chat/completions` 接口, 也用于 Azure OpenAI
+pub struct OpenAIProvider {
+    client: Client<HeadersConfig>,
+    model_config: ModelConfig,
+}
+
+impl OpenAIProvider {
+    pub fn new(model_config: &ModelConfig) -> Result<Self, ProviderError> {
+        Ok(Self {
+            client: create_client(model_config)?,
+            model_config: model_config.clone(),
+        })
+    }
+}
+
+impl ChatProvider for OpenAIProvider {
+    fn send<'a>(&'a self, request: ChatRequest<'a>) -> BoxFuture<'a, Result<DeltaStream, ProviderError>> {
+        if self.model_config.api == Some(OpenAIApi::Responses) {
+            return self.send_responses(request);
+        }
+        Box::pin(async move {
+            let body = create_request_body(
+                request.messages,
+                request.system_prompt,
+                &self.model_config,
+                request.stream,
+                request.response_format,
+            )?;
+            if !request.stream {
+                log_debug!("Start send chat request.");
+                let response = self
+                    .client
+                    .chat()
+                    .create_byot::<_, ChatCompletionResponse>(body)
+                    .await
+                    .map_err(api_error)?;
+                log_debug!("Received chat response.");
+                let mut deltas = Vec::new();
+                if let Some(choice) = response.choices.into_iter().next() {
+                    deltas.extend(choice.message.reasoning().map(ChatDelta::Reasoning));
+                    let content = choice.message.content.unwrap_or(String::from("null"));
+                    deltas.push(ChatDelta::Content(content));
+                    deltas.extend(choice.finish_reason.map(|reason| ChatDelta::Finish(reason.into())));
+                }
+                deltas.extend(response.usage.as_ref().map(|usage| ChatDelta::Usage(usage.into())));
+                return Ok(stream::iter(deltas.into_iter().map(Ok)).boxed());
+            }
+
+            let stream = self
+                .client
+                .chat()
+                .create_stream_byot::<_, ChatCompletionChunk>(body)
+                .await?;
+            Ok(stream
+                .flat_map(|chunk| stream::iter(flatten_deltas(chunk.map(chunk_deltas).map_err(Into::into))))
+                .boxed())
+        })
+    }
+}
+
+impl OpenAIProvider {
+    /// 使用 `/responses` 接口
+    fn send_responses<'a>(&'a self, request: ChatRequest<'a>) -> BoxFuture<'a, Result<DeltaStream, ProviderError>> {
+        Box::pin(async move {
+            let body = responses::create_request_body(
+                request.messages,
+                request.system_prompt,
+                &self.model_config,
+                request.stream,
+                request.response_format,
+            )?;
+            if !request.stream {
+                log_debug!("Start send responses request.");
+                let response = self
+                    .client
+                    .responses()
+                    .create_byot::<_, responses::Response>(body)
+                    .await
+                    .map_err(api_error)?;
+                log_debug!("Received responses response.");
+                return Ok(stream::iter(response.deltas().into_iter().map(Ok)).boxed());
+            }
+
+            // Responses 流末尾没有 `[DONE]`, async-openai 会把连接关闭当作异常并重新发送请求,
+            // 因此流式请求直接用 reqwest 发送, 地址和认证信息仍来自 client 的配置
+            let config = self.client.config();
+            let mut url = reqwest::Url::parse(&config.url("/responses"))?;
+            if !config.query().is_empty() {
+                url.query_pairs_mut().extend_pairs(config.query());
+            }
+            let response = post_json(&reqwest::Client::new(), url.into(), config.headers(), &body, "OpenAI").await?;
+            let stream = response.bytes_stream().eventsource().flat_map(|event| {
+                let deltas = event.map_err(ProviderError::from).and_then(|event| {
+                    log_trace!("responses event data: {}", event.data);
+                    responses::event_deltas(serde_json::from_str(&event.data)?)
+                });
+                stream::iter(flatten_deltas(deltas))
+            });
+            Ok(stream.boxed())
+        })
+    }
+}
+
+/// 接口拒绝结构化输出参数时转换为 [`ResponseFormatRejected`]
+fn api_error(e: OpenAIError) -> ProviderError {
+    match &e {
+        OpenAIError::ApiError(error)
+            if error.param.as_deref() == Some("response_format") || mentions_response_format(&error.message) =>
+        {
+            ResponseFormatRejected(e.to_string()).into()
+        }
+        _ => e.into(),
+    }
+}
+
+/// 流式分片中的事件, 设置了 n > 1 时只取第一个候选
+fn chunk_deltas(chunk: ChatCompletionChunk) -> Vec<ChatDelta> {
+    let mut deltas = Vec::new();
+    for choice in chunk.choices.into_iter().filter(|c| c.index == 0) {
+        deltas.extend(choice.delta.reasoning().map(ChatDelta::Reasoning));
+        deltas.extend(choice.delta.content.map(ChatDelta::Content));
+        deltas.extend(choice.finish_reason.map(|reason| ChatDelta::Finish(reason.into())));
+    }
+    deltas.extend(chunk.usage.as_ref().map(|usage| ChatDelta::Usage(usage.into())));
+    deltas
+}
+
+/// chat completions 响应, 只保留用到的字段
+///
+/// async-openai 的类型没有 `reasoning_content`/`reasoning`, 这两个字段由 DeepSeek、OpenRouter、vLLM 等返回
+#[derive(Debug, Deserialize)]
+struct ChatCompletionResponse {
+    choices: Vec<ResponseChoice>,
+    usage: Option<CompletionUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseChoice {
+    message: ReplyMessage,
+    finish_reason: Option<async_openai::types::FinishReason>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionChunk {
+    choices: Vec<ChunkChoice>,
+    usage: Option<CompletionUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkChoice {
+    index: u32,
+    delta: ReplyMessage,
+    finish_reason: Option<async_openai::types::FinishReason>,
+}
+
+/// 完整回复中的 message 或流式分片中的 delta
+#[derive(Debug, Deserialize)]
+struct ReplyMessage {
+    content: Option<String>,
+    reasoning_content: Option<String>,
+    reasoning: Option<String>,
+}
+
+impl ReplyMessage {
+    fn reasoning(&self) -> Option<String> {
+        self.reasoning_content
+            .as_ref()
+            .or(self.reasoning.as_ref())
+            .filter(|reasoning| !reasoning.is_empty())
+            .cloned()
+    }
+}
+
+fn create_client(model_config: &ModelConfig) -> Result<Client<HeadersConfig>, ProviderError> {
+    let final_api_key = resolve_api_key(model_config, env_var);
+    let base_url = model_config.base_url.as_ref().unwrap();
+    let inner: Box<dyn Config> = match &model_config.provider {
+        Some(Provider::Azure {
+            deployment_id,
+            api_version,
+        }) => {
+            log_debug!("use azure deployment '{}', api-version {}.", deployment_id, api_version);
+            Box::new(
+                AzureConfig::new()
+                    .with_api_key(final_api_key)
+                    .with_api_base(base_url.trim_end_matches('/'))
+                    .with_deployment_id(deployment_id)
+                    .with_api_version(api_version),
+            )
+        }
+        _ => Box::new(
+            OpenAIConfig::default()
+                .with_api_key(final_api_key)
+                .with_api_base(base_url),
+        ),
+    };
+    Ok(Client::with_config(HeadersConfig {
+        inner,
+        headers: create_headers(model_config)?,
+    }))
+}
+
+/// 在 OpenAIConfig/AzureConfig 的基础上附加自定义请求头, 同名时覆盖默认请求头
+struct HeadersConfig {
+    inner: Box<dyn Config>,
+    headers: HeaderMap,
+}
+
+impl Config for HeadersConfig {
+    fn headers(&self) -> HeaderMap {
+        let mut headers = self.inner.headers();
+        headers.extend(self.headers.clone());
+        headers
+    }
+
+    fn url(&self, path: &str) -> String {
+        self.inner.url(path)
+    }
+
+    fn query(&self) -> Vec<(&str, &str)> {
+        self.inner.query()
+    }
+
+    fn api_base(&self) -> &str {
+        self.inner.api_base()
+    }
+
+    fn api_key(&self) -> &SecretString {
+        self.inner.api_key()
+    }
+}
+
+/// 生成请求体, 并合并模型配置中的 extra_body
+fn create_request_body(
+    messages: &[ChatMessage],
+    system_prompt: &str,
+    model_config: &ModelConfig,
+    stream: bool,
+    response_format: Option<&provider::ResponseFormat>,
+) -> Result<Value, serde_json::Error> {
+    let request = create_request(messages, system_prompt, model_config, stream, response_format);
+    let mut body = to_json_value(&request)?;
+    if let Some(extra_body) = &model_config.extra_body {
+        merge_json(&mut body, extra_body);
+    }
+    log_trace!("request body: {}", body);
+    Ok(body)
+}
+
+fn create_request(
+    messages: &[ChatMessage],
+    system_prompt: &str,
+    model_config: &ModelConfig,
+    stream: bool,
+    response_format: Option<&provider::ResponseFormat>,
+) -> CreateChatCompletionRequest {
+    let mut builder = CreateChatCompletionRequestArgs::default();
+    builder.model(model_config.model_name.as_ref().unwrap());
+    builder.stream(stream);
+    if stream {
+        // 最后一个 chunk 会携带整个请求的 token 用量
+        builder.stream_options(ChatCompletionStreamOptions { include_usage: true });
+    }
+
+    if let Some(val) = model_config.temperature {
+        builder.temperature(val);
+    }
+    if let Some(val) = model_config.max_tokens {
+        #[allow(deprecated)]
+        builder.max_tokens(val);
+    }
+    if let Some(val) = model_config.max_completion_tokens {
+        builder.max_completion_tokens(val);
+    }
+    if let Some(val) = model_config.top_p {
+        builder.top_p(val);
+    }
+    if let Some(val) = model_config.frequency_penalty {
+        builder.frequency_penalty(val);
+    }
+    if let Some(val) = model_config.presence_penalty {
+        builder.presence_penalty(val);
+    }
+    if let Some(val) = &model_config.stop {
+        builder.stop(Stop::StringArray(val.clone()));
+    }
+    if let Some(val) = model_config.seed {
+        builder.seed(val);
+    }
+    if let Some(val) = model_config.n {
+        builder.n(val);
+    }
+    if let Some(val) = model_config.reasoning_effort {
+        builder.reasoning_effort(match val {
+            config::ReasoningEffort::Minimal => ReasoningEffort::Minimal,
+            config::ReasoningEffort::Low => ReasoningEffort::Low,
+            config::ReasoningEffort::Medium => ReasoningEffort::Medium,
+            config::ReasoningEffort::High => ReasoningEffort::High,
+        });
+    }
+
+    if let Some(format) = response_format {
+        builder.response_format(match format {
+            provider::ResponseFormat::JsonSchema { name, schema } => ResponseFormat::JsonSchema {
+                json_schema: ResponseFormatJsonSchema {
+                    description: None,
+                    name: name.clone(),
+                    schema: Some(schema.clone()),
+                    strict: None,
+                },
+            },
+            provider::ResponseFormat::JsonObject => ResponseFormat::JsonObject,
+        });
+    }
+
+    let mut request_messages: Vec<ChatCompletionRequestMessage> = vec![
+        ChatCompletionRequestSystemMessageArgs::default()
+            .content(system_prompt)
+            .build()
+            .unwrap()
+            .into(),
+    ];
+    for message in messages {
+        let request_message = match message.role {
+            ChatRole::User => ChatCompletionRequestUserMessageArgs::default()
+                .content(user_content(message))
+                .build()
+                .unwrap()
+                .into(),
+            ChatRole::Assistant => ChatCompletionRequestAssistantMessageArgs::default()
+                .content(message.content.as_str())
+                .build()
+                .unwrap()
+                .into(),
+        };
+        request_messages.push(request_message);
+    }
+
+    builder.messages(request_messages).build().unwrap()
+}
+
+/// 用户消息内容, 带图片时为文本和 `image_url` 组成的数组
+fn user_content(message: &ChatMessage) -> ChatCompletionRequestUserMessageContent {
+    if message.images.is_empty() {
+        return message.content.as_str().into();
+    }
+    let mut parts = vec![ChatCompletionRequestMessageContentPartText::from(message.content.as_str()).into()];
+    parts.extend(message.images.iter().map(|image| {
+        ChatCompletionRequestMessageContentPartImage {
+            image_url: ImageUrl {
+                url: image.data_url(),
+                detail: Some(ImageDetail::Auto),
+            },
+        }
+        .into()
+    }));
+    ChatCompletionRequestUserMessageContent::Array(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::ChatImage;
+    use serde_json::json;
+
+    #[test]
+    fn test_api_error() {
+        let error = |message: &str, param: Option<&str>| {
+            OpenAIError::ApiError(async_openai::error::ApiError {
+                message: message.to_string(),
+                r#type: Some("invalid_request_error".to_string()),
+                param: param.map(str::to_string),
+                code: None,
+            })
+        };
+        assert!(api_error(error("Invalid schema", Some("response_format"))).is::<ResponseFormatRejected>());
+        assert!(
+            api_error(error("'json_schema' is not supported with this model.", None)).is::<ResponseFormatRejected>()
+        );
+        assert!(!api_error(error("Incorrect API key provided", None)).is::<ResponseFormatRejected>());
+    }
+
+    #[test]
+    fn test_create_request_with_images() {
+        let model_config = ModelConfig {
+            model_name: Some("gpt-4o".to_string()),
+            ..Default::default()
+        };
+        let message = ChatMessage::user("what is this?").with_images(vec![ChatImage {
+            mime_type: "image/png".to_string(),
+            data: "iVBO".to_string(),
+        }]);
+        let body = create_request_body(&[message], "be concise", &model_config, false, None).unwrap();
+        assert_eq!(
+            body["messages"][1]["content"],
+            json!([
+                {"type": "text", "text": "what is this?"},
+                {"type": "image_url", "image_url": {"url": "data:image/png;base64,iVBO", "detail": "auto"}}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_chunk_deltas_with_reasoning() {
+        let chunk = |delta: Value| -> ChatCompletionChunk {
+            serde_json::from_value(json!({"choices": [{"index": 0, "delta": delta, "finish_reason": null}]})).unwrap()
+        };
+        assert_eq!(
+            chunk_deltas(chunk(json!({"reasoning_content": "think", "content": null}))),
+            vec![ChatDelta::Reasoning("think".to_string())]
+        );
+        assert_eq!(
+            chunk_deltas(chunk(json!({"reasoning": "more", "content": ""}))),
+            vec![
+                ChatDelta::Reasoning("more".to_string()),
+                ChatDelta::Content(String::new())
+            ]
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use super::anthropic::AnthropicProvider;
+use super::gemini::GeminiProvider;
+use super::ollama::OllamaProvider;
+use super::openai::OpenAIProvider;
+use super::{ChatMessage, TokenUsage};
+use crate::config::{ModelConfig, Provider};
+use crate::log_debug;
+use crate::utils::{StringUtils, env_var, expand_env_vars};
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::Value;
+use std::error::Error;
+use std::fmt;
+
+/// provider 返回的错误, 可以跨 await 传递
+pub type ProviderError = Box<dyn Error + Send + Sync>;
+
+/// 回复的增量事件流
+pub type DeltaStream = BoxStream<'static, Result<ChatDelta, ProviderError>>;
+
+/// 回复结束的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FinishReason {
+    /// 正常结束或命中 stop
+    Stop,
+    /// 达到 max tokens 上限
+    Length,
+    /// 被内容审核拦截
+    ContentFilter,
+    Other(String),
+}
+
+impl fmt::Display for FinishReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FinishReason::Stop => write!(f, "stop"),
+            FinishReason::Length => write!(f, "length"),
+            FinishReason::ContentFilter => write!(f, "content_filter"),
+            FinishReason::Other(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+/// 回复中的一个增量事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChatDelta {
+    /// 回复文本片段
+    Content(String),
+    /// 推理过程片段, 如 `reasoning_content`
+    Reasoning(String),
+    /// 截至当前的累计用量
+    Usage(TokenUsage),
+    Finish(FinishReason),
+}
+
+/// 结构化输出的格式
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResponseFormat {
+    /// 按 JSON Schema 输出
+    JsonSchema { name: String, schema: Value },
+    /// 只要求输出 JSON 对象, 用于不支持 JSON Schema 的接口
+    JsonObject,
+}
+
+/// 接口以 400 拒绝了请求中的结构化输出参数, 可以换用其他 [`ResponseFormat`] 重试
+#[derive(Debug)]
+pub struct ResponseFormatRejected(pub String);
+
+impl fmt::Display for ResponseFormatRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for ResponseFormatRejected {}
+
+/// 错误信息是否与结构化输出参数有关
+pub fn mentions_response_format(message: &str) -> bool {
+    const KEYWORDS: &[&str] = &[
+        "response_format",
+        "json_schema",
+        "text.format",
+        "responsejsonschema",
+        "response_json_schema",
+        "responsemimetype",
+        "response_mime_type",
+    ];
+    let message = message.to_lowercase();
+    KEYWORDS.iter().any(|keyword| message.contains(keyword))
+}
+
+/// 一次对话请求
+pub struct ChatRequest<'a> {
+    pub messages: &'a [ChatMessage],
+    pub system_prompt: &'a str,
+    pub stream: bool,
+    /// 不支持的 provider (Anthropic) 忽略此项, 只依靠 system prompt 中的要求
+    pub response_format: Option<&'a ResponseFormat>,
+}
+
+/// 对话接口, 每种 [`Provider`] 一个实现
+pub trait ChatProvider: Send + Sync {
+    /// 发送请求并返回增量事件流, 非流式请求时流中是一次完整的回复
+    fn send<'a>(&'a self, request: ChatRequest<'a>) -> BoxFuture<'a, Result<DeltaStream, ProviderError>>;
+}
+
+/// 根据模型配置选择 provider
+pub fn create_provider(model_config: &ModelConfig) -> Result<Box<dyn ChatProvider>, ProviderError> {
+    Ok(match &model_config.provider {
+        Some(Provider::Anthropic) => Box::new(AnthropicProvider::new(model_config)?),
+        Some(Provider::Gemini) => Box::new(GeminiProvider::new(model_config)?),
+        Some(Provider::Ollama) => Box::new(OllamaProvider::new(model_config)?),
+        Some(Provider::OpenAI | Provider::Azure { .. }) | None => Box::new(OpenAIProvider::new(model_config)?),
+    })
+}
+
+/// 最终使用的 api key
+///
+/// OpenAI 兼容接口优先使用环境变量 `OPENAI_API_KEY`; Anthropic 和 Gemini 优先使用配置的 api key,
+/// 未配置时分别读取 `ANTHROPIC_API_KEY` 和 `GEMINI_API_KEY`/`GOOGLE_API_KEY`。
+/// `OPENAI_API_KEY` 不会发送给其他 provider。环境变量通过 `env` 查找, 一般传入 [`env_var`]。
+pub fn resolve_api_key(model_config: &ModelConfig, env: impl Fn(&str) -> Option<String>) -> String {
+    let configured = || model_config.api_key.clone().filter(|key| !key.is_empty());
+    let env_api_key = |name: &str| {
+        let val = env(name).filter(|val| !val.is_empty())?;
+        log_debug!("use env {} as api-key.", name);
+        Some(val)
+    };
+    let final_api_key = match &model_config.provider {
+        Some(Provider::OpenAI | Provider::Azure { .. }) | None => match env("OPENAI_API_KEY") {
+            Some(val) => {
+                log_debug!("use env OPENAI_API_KEY to override api-key.");
+                Some(val)
+            }
+            None => configured(),
+        },
+        Some(Provider::Anthropic) => configured().or_else(|| env_api_key("ANTHROPIC_API_KEY")),
+        Some(Provider::Gemini) => configured()
+            .or_else(|| env_api_key("GEMINI_API_KEY"))
+            .or_else(|| env_api_key("GOOGLE_API_KEY")),
+        Some(Provider::Ollama) => configured(),
+    }
+    .unwrap_or_default();
+    log_debug!("final used api-key: {}", StringUtils::mask_sensitive(&final_api_key));
+    final_api_key
+}
+
+/// 根据模型配置生成自定义请求头, 展开其中的环境变量
+pub fn create_headers(model_config: &ModelConfig) -> Result<HeaderMap, ProviderError> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in model_config.headers.iter().flatten() {
+        let value = expand_env_vars(value, env_var);
+        log_debug!("custom header: {}: {}", name, StringUtils::mask_sensitive(&value));
+        let name = HeaderName::try_from(name.as_str()).map_err(|e| format!("invalid header name '{}': {}", name, e))?;
+        let value = HeaderValue::try_from(value).map_err(|e| format!("invalid value of header '{}': {}", name, e))?;
+        headers.insert(name, value);
+    }
+    Ok(headers)
+}
+
+/// 发送 JSON 请求, 非 2xx 响应转换为错误
+pub async fn post_json(
+    client: &reqwest::Client,
+    url: String,
+    headers: HeaderMap,
+    body: &Value,
+    api_name: &str,
+) -> Result<reqwest::Response, ProviderError> {
+    let response = client.post(url).headers(headers).json(body).send().await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        let message = format!("{} API error {}: {}", api_name, status, api_error_message(&body));
+        if status == StatusCode::BAD_REQUEST && mentions_response_format(&body) {
+            return Err(ResponseFormatRejected(message).into());
+        }
+        return Err(message.into());
+    }
+    Ok(response)
+}
+
+/// 从错误响应中提取 `error.message` 或字符串形式的 `error`, 无法解析时返回原文
+pub fn api_error_message(body: &str) -> String {
+    serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|value| {
+            let error = &value["error"];
+            error["message"].as_str().or(error.as_str()).map(String::from)
+        })
+        .unwrap_or_else(|| body.to_string())
+}
+
+/// 把一个分片解析出的事件展开为流中的多个元素
+pub fn flatten_deltas(deltas: Result<Vec<ChatDelta>, ProviderError>) -> Vec<Result<ChatDelta, ProviderError>> {
+    match deltas {
+        Ok(deltas) => deltas.into_iter().map(Ok).collect(),
+        Err(e) => vec![Err(e)],
+    }
+}
@@ -0,0 +1,306 @@
+use super::provider::{ChatDelta, FinishReason, ProviderError, ResponseFormat};
+use super::{ChatMessage, ChatRole, TokenUsage};
+use crate::config::{ModelConfig, ReasoningEffort};
+use crate::log_trace;
+use crate::utils::{merge_json, to_json_value};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 生成 `/responses` 请求体, system prompt 放在 `instructions`, 对话历史放在 `input`
+pub fn create_request_body(
+    messages: &[ChatMessage],
+    system_prompt: &str,
+    model_config: &ModelConfig,
+    stream: bool,
+    response_format: Option<&ResponseFormat>,
+) -> Result<Value, serde_json::Error> {
+    let request = ResponsesRequest {
+        model: model_config.model_name.as_ref().unwrap(),
+        instructions: system_prompt,
+        input: messages.iter().map(InputMessage::from).collect(),
+        stream,
+        temperature: model_config.temperature,
+        top_p: model_config.top_p,
+        max_output_tokens: model_config.max_completion_tokens.or(model_config.max_tokens),
+        reasoning: model_config.reasoning_effort.map(|effort| Reasoning { effort }),
+        text: response_format.map(|format| TextConfig {
+            format: match format {
+                ResponseFormat::JsonSchema { name, schema } => TextFormat::JsonSchema { name, schema },
+                ResponseFormat::JsonObject => TextFormat::JsonObject,
+            },
+        }),
+    };
+    let mut body = to_json_value(&request)?;
+    if let Some(extra_body) = &model_config.extra_body {
+        merge_json(&mut body, extra_body);
+    }
+    log_trace!("responses request body: {}", body);
+    Ok(body)
+}
+
+/// 解析一个流式事件, 只关心文本、推理摘要增量和最终状态
+pub fn event_deltas(event: StreamEvent) -> Result<Vec<ChatDelta>, ProviderError> {
+    match event.kind.as_str() {
+        "response.output_text.delta" => Ok(event.delta.map(ChatDelta::Content).into_iter().collect()),
+        "response.reasoning_summary_text.delta" | "response.reasoning_text.delta" => {
+            Ok(event.delta.map(ChatDelta::Reasoning).into_iter().collect())
+        }
+        "response.completed" | "response.incomplete" => {
+            Ok(event.response.map(Response::status_deltas).unwrap_or_default())
+        }
+        "response.failed" => {
+            let message = event
+                .response
+                .and_then(|response| response.error)
+                .map(|error| error.message)
+                .unwrap_or_default();
+            Err(format!("Responses API error: {}", message).into())
+        }
+        "error" => Err(format!("Responses API error: {}", event.message.unwrap_or_default()).into()),
+        _ => Ok(Vec::new()),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ResponsesRequest<'a> {
+    model: &'a str,
+    instructions: &'a str,
+    input: Vec<InputMessage<'a>>,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_output_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reasoning: Option<Reasoning>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<TextConfig<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Reasoning {
+    effort: ReasoningEffort,
+}
+
+#[derive(Debug, Serialize)]
+struct TextConfig<'a> {
+    format: TextFormat<'a>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum TextFormat<'a> {
+    JsonSchema { name: &'a str, schema: &'a Value },
+    JsonObject,
+}
+
+#[derive(Debug, Serialize)]
+struct InputMessage<'a> {
+    role: ChatRole,
+    content: InputContent<'a>,
+}
+
+/// 纯文本消息使用字符串, 带图片时使用 `input_text` 和 `input_image` 组成的数组
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum InputContent<'a> {
+    Text(&'a str),
+    Parts(Vec<InputPart<'a>>),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum InputPart<'a> {
+    InputText { text: &'a str },
+    InputImage { image_url: String },
+}
+
+impl<'a> From<&'a ChatMessage> for InputMessage<'a> {
+    fn from(message: &'a ChatMessage) -> Self {
+        let content = if message.images.is_empty() {
+            InputContent::Text(&message.content)
+        } else {
+            let mut parts = vec![InputPart::InputText { text: &message.content }];
+            parts.extend(message.images.iter().map(|image| InputPart::InputImage {
+                image_url: image.data_url(),
+            }));
+            InputContent::Parts(parts)
+        };
+        Self {
+            role: message.role,
+            content,
+        }
+    }
+}
+
+/// 流式事件, 通过 `type` 区分
+#[derive(Debug, Deserialize)]
+pub struct StreamEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    delta: Option<String>,
+    response: Option<Response>,
+    message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Response {
+    #[serde(default)]
+    output: Vec<OutputItem>,
+    status: Option<String>,
+    incomplete_details: Option<IncompleteDetails>,
+    usage: Option<Usage>,
+    error: Option<ErrorBody>,
+}
+
+impl Response {
+    /// 非流式响应: 推理摘要、所有 `output_text` 拼接后的文本、结束原因和用量
+    pub fn deltas(self) -> Vec<ChatDelta> {
+        let summary: String = self
+            .output
+            .iter()
+            .filter(|item| item.kind == "reasoning")
+            .flat_map(|item| &item.summary)
+            .filter_map(|summary| summary.text.as_deref())
+            .collect();
+        let content: String = self
+            .output
+            .iter()
+            .filter(|item| item.kind == "message")
+            .flat_map(|item| &item.content)
+            .filter(|content| content.kind == "output_text")
+            .filter_map(|content| content.text.as_deref())
+            .collect();
+        let mut deltas = Vec::new();
+        if !summary.is_empty() {
+            deltas.push(ChatDelta::Reasoning(summary));
+        }
+        deltas.push(ChatDelta::Content(content));
+        deltas.extend(self.status_deltas());
+        deltas
+    }
+
+    fn status_deltas(self) -> Vec<ChatDelta> {
+        let mut deltas = Vec::new();
+        deltas.extend(self.usage.map(|usage| ChatDelta::Usage(usage.into())));
+        let reason = match self.status.as_deref() {
+            Some("completed") => Some(FinishReason::Stop),
+            Some("incomplete") => Some(match self.incomplete_details.map(|details| details.reason).as_deref() {
+                Some("max_output_tokens") => FinishReason::Length,
+                Some("content_filter") => FinishReason::ContentFilter,
+                Some(other) => FinishReason::Other(other.to_string()),
+                None => FinishReason::Other("incomplete".to_string()),
+            }),
+            _ => None,
+        };
+        deltas.extend(reason.map(ChatDelta::Finish));
+        deltas
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OutputItem {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    content: Vec<OutputContent>,
+    /// `reasoning` 项的推理摘要
+    #[serde(default)]
+    summary: Vec<OutputContent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OutputContent {
+    #[serde(rename = "type")]
+    kind: String,
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IncompleteDetails {
+    reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorBody {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Usage {
+    #[serde(default)]
+    input_tokens: u32,
+    #[serde(default)]
+    output_tokens: u32,
+    #[serde(default)]
+    total_tokens: u32,
+}
+
+impl From<Usage> for TokenUsage {
+    fn from(usage: Usage) -> Self {
+        Self {
+            prompt_tokens: usage.input_tokens,
+            completion_tokens: usage.output_tokens,
+            total_tokens: usage.total_tokens,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_create_request_body() {
+        let model_config = ModelConfig {
+            model_name: Some("gpt-5".to_string()),
+            max_completion_tokens: Some(256),
+            reasoning_effort: Some(ReasoningEffort::Low),
+            ..Default::default()
+        };
+        let body = create_request_body(&[ChatMessage::user("hi")], "be concise", &model_config, true, None).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "model": "gpt-5",
+                "instructions": "be concise",
+                "input": [{"role": "user", "content": "hi"}],
+                "stream": true,
+                "max_output_tokens": 256,
+                "reasoning": {"effort": "low"}
+            })
+        );
+    }
+
+    #[test]
+    fn test_event_deltas() {
+        let event = |value: Value| serde_json::from_value::<StreamEvent>(value).unwrap();
+        let delta = event(json!({"type": "response.output_text.delta", "delta": "hi"}));
+        assert_eq!(event_deltas(delta).unwrap(), vec![ChatDelta::Content("hi".to_string())]);
+        let incomplete = event(json!({
+            "type": "response.incomplete",
+            "response": {
+                "status": "incomplete",
+                "incomplete_details": {"reason": "max_output_tokens"},
+                "usage": {"input_tokens": 10, "output_tokens": 4, "total_tokens": 14}
+            }
+        }));
+        assert_eq!(
+            event_deltas(incomplete).unwrap(),
+            vec![
+                ChatDelta::Usage(TokenUsage {
+                    prompt_tokens: 10,
+                    completion_tokens: 4,
+                    total_tokens: 14
+                }),
+                ChatDelta::Finish(FinishReason::Length)
+            ]
+        );
+        let ignored = event(json!({"type": "response.created", "response": {"status": "in_progress"}}));
+        assert!(event_deltas(ignored).unwrap().is_empty());
+        assert!(event_deltas(event(json!({"type": "error", "message": "boom"}))).is_err());
+    }
+}
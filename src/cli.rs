@@ -1,4 +1,6 @@
+#[allow(clippy::module_inception)]
 pub mod cli;
 pub mod interactive;
+pub mod repl;
 pub mod response_render;
 pub mod structs;
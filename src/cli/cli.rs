@@ -1,12 +1,23 @@
 use std::io::{self, IsTerminal, Read};
+use std::path::PathBuf;
 use std::process::exit;
 
-use crate::cli::interactive::interactive_input;
-use crate::cli::structs::{Cli, Commands, DeleteCommands, SetCommands, UseCommands};
+use crate::cli::repl::run_repl;
+use crate::cli::structs::{
+    Cli, Commands, DeleteCommands, ModelsCommands, ProviderKind, SessionCommands, SetCommands, UseCommands,
+};
 
+use crate::attachment::{self, FileAttachments, MessageAttachments};
+use crate::chat::{ChatMessage, ResponseFormat, ResponseFormatRejected};
 use crate::config::{
-    Config, ConfigBuilder, ConfigManager, ModelConfig, PromptConfig, merge_config, print_models, print_prompts,
+    BudgetConfig, CommandRisk, CommandRule, Config, ConfigBuilder, ConfigManager, DEFAULT_AZURE_API_VERSION,
+    ModelConfig, PromptConfig, Provider, merge_config, print_budgets, print_command_rules, print_model_detail,
+    print_models, print_prompts,
 };
+use crate::conversation::{Conversation, ConversationManager, print_conversation, print_conversations};
+use crate::ledger::{BUDGET_EXCEEDED_EXIT_CODE, BudgetExceeded, LedgerManager, StatsPeriod, StatsReport, print_stats};
+use crate::schema::{JsonSchema, SCHEMA_MISMATCH_EXIT_CODE};
+use crate::shell::{self, CommandAction, ShellEnv};
 use crate::utils::StringUtilsTrait;
 use crate::utils::logger::set_log_level;
 use crate::{chat, log_debug, utils};
@@ -16,12 +27,16 @@ use utils::logger::{self};
 
 pub async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut custom_args = std::env::args().collect::<Vec<_>>();
+    let mut piped_input = None;
 
     if !io::stdin().is_terminal() {
         //if has pipe stdin
         let mut input = String::new();
         io::stdin().read_to_string(&mut input).unwrap_or_default();
-        custom_args.push(input.trim().to_string());
+        if !input.trim().is_empty() {
+            custom_args.push(input.trim().to_string());
+            piped_input = Some(input);
+        }
     }
 
     let cli = Cli::parse_from(custom_args);
@@ -53,12 +68,60 @@ pub async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Some(Commands::Delete { config }) => {
             handle_delete_command(&mut file_config, &mut config_manager, config).await?;
         }
-        Some(Commands::List { config_type }) => {
-            handle_list_command(&mut file_config, config_type).await?;
+        Some(Commands::List { config_type, name }) => {
+            handle_list_command(&mut file_config, config_type, name.as_deref()).await?;
+        }
+        Some(Commands::Stats { period, json }) => {
+            handle_stats_command(*period, *json).await?;
+        }
+        Some(Commands::Session { command }) => {
+            handle_session_command(&runtime_config, &cli, command).await?;
+        }
+        Some(Commands::Models { command }) => {
+            handle_models_command(file_config, &mut config_manager, command).await?;
+        }
+        Some(Commands::Cmd { print, input }) => {
+            handle_cmd_command(&runtime_config, input, *print, cli.ignore_budget).await?;
+        }
+        Some(Commands::Fix { rerun, command }) => {
+            // 管道输入被追加为最后一个参数, 作为命令的输出
+            let command = match piped_input {
+                Some(_) => &command[..command.len().saturating_sub(1)],
+                None => &command[..],
+            };
+            handle_fix_command(
+                &runtime_config,
+                command,
+                piped_input.as_deref(),
+                *rerun,
+                cli.ignore_budget,
+            )
+            .await?;
+        }
+        Some(Commands::Run { question, command }) => {
+            // 管道输入被追加为最后一个参数, 作为命令的 stdin
+            let command = match piped_input {
+                Some(_) => &command[..command.len().saturating_sub(1)],
+                None => &command[..],
+            };
+            handle_run_command(
+                &runtime_config,
+                command,
+                piped_input.as_deref(),
+                question.as_deref(),
+                cli.ignore_budget,
+            )
+            .await?;
+        }
+        Some(Commands::ShellInit { shell }) => {
+            print!("{}", shell::init_script(*shell));
+        }
+        Some(Commands::Chat) => {
+            handle_chat_command(&runtime_config, &cli).await?;
         }
         None => {
             log_debug!("match None Command");
-            handle_chat_command(&runtime_config, &mut &cli).await?;
+            handle_chat_command(&runtime_config, &cli).await?;
         }
     }
 
@@ -73,25 +136,49 @@ async fn handle_set_command(
     match set_command {
         SetCommands::Model {
             name,
+            provider,
+            api,
+            deployment_id,
+            api_version,
             base_url,
             model_name,
             api_key,
-            temperature,
+            sampling,
+            extra_body,
+            headers,
+            input_price_per_mtok,
+            output_price_per_mtok,
         } => {
+            let raw_model = file_config.models.get(name);
+            let provider = match resolve_provider(
+                *provider,
+                deployment_id,
+                api_version,
+                model_name.as_ref().or(raw_model.and_then(|m| m.model_name.as_ref())),
+                raw_model.and_then(|m| m.provider.as_ref()),
+            ) {
+                Ok(provider) => provider,
+                Err(e) => {
+                    eprintln!("{}", format!("❌{}", e).red());
+                    return Ok(());
+                }
+            };
             let mut new_model = ModelConfig {
+                provider,
+                api: *api,
                 base_url: base_url.clone(),
                 model_name: model_name.clone(),
                 api_key: api_key.clone(),
-                temperature: temperature.clone(),
-            };
-            let raw_model = file_config.models.get(name).clone();
-            match raw_model {
-                Some(raw_model) => {
-                    //merged
-                    new_model = new_model.merge_with(raw_model);
-                }
-                None => {}
+                extra_body: extra_body.clone(),
+                headers: (!headers.is_empty()).then(|| headers.iter().cloned().collect()),
+                input_price_per_mtok: *input_price_per_mtok,
+                output_price_per_mtok: *output_price_per_mtok,
+                ..sampling.to_model_config()
             };
+            if let Some(raw_model) = file_config.models.get(name) {
+                //merged
+                new_model = new_model.merge_with(raw_model);
+            }
             file_config.models.insert(name.clone(), new_model);
             config_manager.save(file_config)?;
             // config_manager.save(
@@ -109,10 +196,95 @@ async fn handle_set_command(
             config_manager.save(file_config)?;
             println!("{}", format!("Prompt configuration '{}' has been set.", name).green());
         }
+        SetCommands::Budget {
+            model,
+            daily_tokens,
+            monthly_tokens,
+            daily_cost,
+            monthly_cost,
+            action,
+        } => {
+            let new_budget = BudgetConfig {
+                daily_tokens: *daily_tokens,
+                monthly_tokens: *monthly_tokens,
+                daily_cost: *daily_cost,
+                monthly_cost: *monthly_cost,
+                action: *action,
+            };
+            let budget = match model {
+                Some(name) => match file_config.models.get_mut(name) {
+                    Some(model_config) => &mut model_config.budget,
+                    None => {
+                        eprintln!("{}", format!("Model configuration '{}' not found.", name).red());
+                        return Ok(());
+                    }
+                },
+                None => &mut file_config.budget,
+            };
+            *budget = Some(match budget.take() {
+                Some(raw_budget) => new_budget.merge_with(&raw_budget),
+                None => new_budget,
+            });
+            config_manager.save(file_config)?;
+            match model {
+                Some(name) => println!("{}", format!("Budget of model '{}' has been set.", name).green()),
+                None => println!("{}", "Global budget has been set.".green()),
+            }
+        }
     }
     Ok(())
 }
 
+/// 根据 `set model` 的参数和已有配置确定接口类型
+fn resolve_provider(
+    kind: Option<ProviderKind>,
+    deployment_id: &Option<String>,
+    api_version: &Option<String>,
+    model_name: Option<&String>,
+    raw_provider: Option<&Provider>,
+) -> Result<Option<Provider>, String> {
+    let kind = kind.or(match raw_provider {
+        Some(Provider::OpenAI) => Some(ProviderKind::OpenAI),
+        Some(Provider::Azure { .. }) => Some(ProviderKind::Azure),
+        Some(Provider::Anthropic) => Some(ProviderKind::Anthropic),
+        Some(Provider::Gemini) => Some(ProviderKind::Gemini),
+        Some(Provider::Ollama) => Some(ProviderKind::Ollama),
+        None => None,
+    });
+    match kind {
+        Some(ProviderKind::Azure) => {
+            let (raw_deployment_id, raw_api_version) = match raw_provider {
+                Some(Provider::Azure {
+                    deployment_id,
+                    api_version,
+                }) => (Some(deployment_id), Some(api_version)),
+                _ => (None, None),
+            };
+            let deployment_id = deployment_id
+                .as_ref()
+                .or(raw_deployment_id)
+                .or(model_name)
+                .ok_or("Azure provider requires --deployment-id or --model-name.")?;
+            let api_version = api_version
+                .clone()
+                .or(raw_api_version.cloned())
+                .unwrap_or(DEFAULT_AZURE_API_VERSION.to_string());
+            Ok(Some(Provider::Azure {
+                deployment_id: deployment_id.clone(),
+                api_version,
+            }))
+        }
+        _ if deployment_id.is_some() || api_version.is_some() => {
+            Err("--deployment-id and --api-version are only valid with --provider azure.".to_string())
+        }
+        Some(ProviderKind::OpenAI) => Ok(Some(Provider::OpenAI)),
+        Some(ProviderKind::Anthropic) => Ok(Some(Provider::Anthropic)),
+        Some(ProviderKind::Gemini) => Ok(Some(Provider::Gemini)),
+        Some(ProviderKind::Ollama) => Ok(Some(Provider::Ollama)),
+        None => Ok(None),
+    }
+}
+
 async fn handle_use_command(
     file_config: &mut Config,
     config_manager: &mut ConfigManager,
@@ -120,7 +292,7 @@ async fn handle_use_command(
 ) -> Result<(), Box<dyn std::error::Error>> {
     match use_command {
         UseCommands::Model { name } => {
-            if file_config.models.get(name).is_some() {
+            if file_config.models.contains_key(name) {
                 file_config.default_model = Some(name.clone());
                 config_manager.save(file_config)?;
                 println!("{}", format!("Default model has been set to '{}'.", name).green());
@@ -129,7 +301,7 @@ async fn handle_use_command(
             }
         }
         UseCommands::Prompt { name } => {
-            if file_config.prompts.get(name).is_some() {
+            if file_config.prompts.contains_key(name) {
                 file_config.default_prompt = Some(name.clone());
                 config_manager.save(file_config)?;
                 println!("{}", format!("Default prompt has been set to '{}'.", name).green());
@@ -163,11 +335,37 @@ async fn handle_delete_command(
                 format!("Prompt configuration '{}' has been deleted.", name).green()
             );
         }
+        DeleteCommands::Budget { model: Some(name) } => {
+            let Some(model_config) = file_config.models.get_mut(name) else {
+                eprintln!("{}", format!("Model configuration '{}' not found.", name).red());
+                return Ok(());
+            };
+            model_config.budget = None;
+            config_manager.save(file_config)?;
+            println!("{}", format!("Budget of model '{}' has been deleted.", name).green());
+        }
+        DeleteCommands::Budget { model: None } => {
+            file_config.budget = None;
+            config_manager.save(file_config)?;
+            println!("{}", "Global budget has been deleted.".green());
+        }
     }
     Ok(())
 }
 
-async fn handle_list_command(file_config: &mut Config, config_type: &String) -> Result<(), Box<dyn std::error::Error>> {
+async fn handle_list_command(
+    file_config: &mut Config,
+    config_type: &String,
+    name: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(name) = name {
+        match file_config.models.get(name) {
+            Some(model) => print_model_detail(name, model, file_config.default_model.as_deref() == Some(name)),
+            None => eprintln!("{}", format!("Model configuration '{}' not found.", name).red()),
+        }
+        return Ok(());
+    }
+
     if config_type == "models" || config_type == "model" || config_type == "all" {
         print_models(file_config)?;
     }
@@ -178,15 +376,140 @@ async fn handle_list_command(file_config: &mut Config, config_type: &String) ->
         print_prompts(file_config);
     }
 
+    if config_type == "budgets" || config_type == "budget" || config_type == "all" {
+        print_budgets(file_config);
+    }
+
+    if config_type == "rules" || config_type == "rule" || config_type == "all" {
+        print_command_rules(file_config);
+    }
+
     let config_path = ConfigManager::get_config_dir()?.join("config.json");
     println!("config file location: {}", config_path.display().to_string().cyan());
 
     Ok(())
 }
 
-async fn handle_chat_command(runtime_config: &Config, cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
-    let model_name = runtime_config.default_model.clone();
-    let prompt_name = runtime_config.default_prompt.clone();
+async fn handle_stats_command(period: StatsPeriod, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let config_dir = ConfigManager::get_config_dir()?;
+    let entries = LedgerManager::new(&config_dir)?.load()?;
+    let report = StatsReport::new(&entries, period);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_stats(&report)?;
+        println!(
+            "ledger file location: {}",
+            config_dir.join("usage.jsonl").display().to_string().cyan()
+        );
+    }
+    Ok(())
+}
+
+/// 加载对话, 未找到或id不唯一时提示并退出
+fn load_conversation_or_exit(conversation_manager: &ConversationManager, id: &str) -> Conversation {
+    conversation_manager.load(id).unwrap_or_else(|e| {
+        eprintln!("{}", format!("❌{}", e).red());
+        exit(1);
+    })
+}
+
+async fn handle_models_command(
+    file_config: Config,
+    config_manager: &mut ConfigManager,
+    models_command: &ModelsCommands,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match models_command {
+        ModelsCommands::ImportOllama { base_url, prefix } => {
+            let model_names = match chat::list_ollama_models(base_url).await {
+                Ok(model_names) => model_names,
+                Err(e) => {
+                    eprintln!(
+                        "{}",
+                        format!("❌Failed to list Ollama models from {}: {}", base_url, e).red()
+                    );
+                    exit(1);
+                }
+            };
+            if model_names.is_empty() {
+                println!("{}", "No local Ollama models found.".yellow());
+                return Ok(());
+            }
+
+            let mut builder = ConfigBuilder::new(file_config);
+            for model_name in &model_names {
+                let name = format!("{}{}", prefix, model_name);
+                builder = builder.set_model(
+                    name.clone(),
+                    ModelConfig {
+                        provider: Some(Provider::Ollama),
+                        model_name: Some(model_name.clone()),
+                        base_url: Some(base_url.clone()),
+                        ..Default::default()
+                    },
+                );
+                println!("  {} {}", name.blue(), model_name.clone().dark_grey());
+            }
+            config_manager.save(&builder.build())?;
+            println!(
+                "{}",
+                format!(
+                    "{} Ollama model configuration(s) have been imported.",
+                    model_names.len()
+                )
+                .green()
+            );
+        }
+    }
+    Ok(())
+}
+
+async fn handle_session_command(
+    runtime_config: &Config,
+    cli: &Cli,
+    session_command: &SessionCommands,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conversation_manager = ConversationManager::new(&ConfigManager::get_config_dir()?)?;
+    match session_command {
+        SessionCommands::List => {
+            print_conversations(&conversation_manager.list()?)?;
+        }
+        SessionCommands::Show { id } => {
+            print_conversation(&load_conversation_or_exit(&conversation_manager, id));
+        }
+        SessionCommands::Resume { id } => {
+            let conversation = load_conversation_or_exit(&conversation_manager, id);
+            chat_in_conversation(runtime_config, cli, Some(conversation), &conversation_manager).await?;
+        }
+        SessionCommands::Rename { id, title } => {
+            let mut conversation = load_conversation_or_exit(&conversation_manager, id);
+            conversation.title = Some(title.clone());
+            conversation_manager.save(&conversation)?;
+            println!(
+                "{}",
+                format!("Session '{}' has been renamed to '{}'.", conversation.id, title).green()
+            );
+        }
+        SessionCommands::Delete { id } => {
+            let conversation = load_conversation_or_exit(&conversation_manager, id);
+            conversation_manager.delete(&conversation.id)?;
+            println!("{}", format!("Session '{}' has been deleted.", conversation.id).green());
+        }
+    }
+    Ok(())
+}
+
+/// 预算超限时提示并以专用退出码退出, 其他错误原样返回
+fn exit_on_budget_exceeded(e: Box<dyn std::error::Error>) -> Box<dyn std::error::Error> {
+    if let Some(exceeded) = e.downcast_ref::<BudgetExceeded>() {
+        eprintln!("{}", format!("❌{}", exceeded).red());
+        exit(BUDGET_EXCEEDED_EXIT_CODE);
+    }
+    e
+}
+
+/// 解析当前使用的模型配置, 未找到时提示并退出
+fn resolve_model_config(runtime_config: &Config) -> (&str, &ModelConfig) {
     let model_hint = format!(
         "{} to list,\n{} to set, \n{} to specify default, \n{} to temporarily specify.",
         "aichat config list model".dark_green(),
@@ -195,24 +518,13 @@ async fn handle_chat_command(runtime_config: &Config, cli: &Cli) -> Result<(), B
         "aichat use model <MODEL_CONFIG_NAME>".dark_green(),
         "-m <MODEL_CONFIG_NAME>".dark_green()
     );
-    let prompt_hint = format!(
-        "{} to list,\n{} to set, \n{} to specify default, \n{} to temporarily specify.",
-        "aichat config list prompt".dark_green(),
-        "aichat set prompt <PROMPT_CONFIG_NAME> --content <PROMPT_CONTENT>".dark_green(),
-        "aichat use prompt <PROMPT_CONFIG_NAME>".dark_green(),
-        "-p <PROMPT_CONFIG_NAME>".dark_green()
-    );
-    if matches!(model_name.as_deref(), None | Some("")) {
-        eprintln!("❌ No model config specified, please:\n{}", model_hint);
-        exit(78);
-    }
-    if matches!(prompt_name.as_deref(), None | Some("")) {
-        eprintln!("❌ No prompt config specified, please:\n{}", prompt_hint);
-        exit(78);
-    }
-
-    let model_name: &str = model_name.as_ref().unwrap();
-    let prompt_name: &str = prompt_name.as_ref().unwrap();
+    let model_name = match runtime_config.default_model.as_deref() {
+        None | Some("") => {
+            eprintln!("❌ No model config specified, please:\n{}", model_hint);
+            exit(78);
+        }
+        Some(name) => name,
+    };
 
     let model_config = runtime_config.models.get(model_name).unwrap_or_else(|| {
         eprintln!(
@@ -223,6 +535,27 @@ async fn handle_chat_command(runtime_config: &Config, cli: &Cli) -> Result<(), B
         std::process::exit(78);
     });
 
+    (model_name, model_config)
+}
+
+/// 解析当前使用的模型和提示配置, 未找到时提示并退出
+fn resolve_chat_configs(runtime_config: &Config) -> (&str, &ModelConfig, &str, &PromptConfig) {
+    let (model_name, model_config) = resolve_model_config(runtime_config);
+    let prompt_hint = format!(
+        "{} to list,\n{} to set, \n{} to specify default, \n{} to temporarily specify.",
+        "aichat config list prompt".dark_green(),
+        "aichat set prompt <PROMPT_CONFIG_NAME> --content <PROMPT_CONTENT>".dark_green(),
+        "aichat use prompt <PROMPT_CONFIG_NAME>".dark_green(),
+        "-p <PROMPT_CONFIG_NAME>".dark_green()
+    );
+    let prompt_name = match runtime_config.default_prompt.as_deref() {
+        None | Some("") => {
+            eprintln!("❌ No prompt config specified, please:\n{}", prompt_hint);
+            exit(78);
+        }
+        Some(name) => name,
+    };
+
     let prompt_config = runtime_config.prompts.get(prompt_name).unwrap_or_else(|| {
         eprintln!(
             "❌Prompt configuration '{}' not found, please:\n{}",
@@ -232,14 +565,337 @@ async fn handle_chat_command(runtime_config: &Config, cli: &Cli) -> Result<(), B
         std::process::exit(78);
     });
 
-    // If input is empty,(interactive mode) wait for input, then call single_message
-    let input = if cli.input.is_empty() {
-        let input = interactive_input().await?;
-        input
+    (model_name, model_config, prompt_name, prompt_config)
+}
+
+/// 生成一条 shell 命令, 确认后在用户的 shell 中执行, 并以命令的退出码退出
+///
+/// `print_only` 时只输出命令, 供 shell 集成脚本替换命令行使用。
+async fn handle_cmd_command(
+    runtime_config: &Config,
+    input: &[String],
+    print_only: bool,
+    ignore_budget: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let input = input.join(" ");
+    if input.trim().is_empty() {
+        println!("{}", "⚠ Input message is empty.".yellow());
+        exit(1);
+    }
+    let (model_name, model_config) = resolve_model_config(runtime_config);
+    let env = ShellEnv::detect();
+    let prompt_config = PromptConfig {
+        content: shell::command_prompt(&env),
+    };
+
+    log_debug!("Generate command for {} on {}: {}", env.shell_name(), env.os, input);
+    let response = chat::completion(
+        &[ChatMessage::user(input)],
+        model_name.to_string(),
+        model_config,
+        shell::CMD_PROMPT_NAME.to_string(),
+        &prompt_config,
+        runtime_config,
+        ignore_budget,
+        // 回复由这里高亮显示, 不经过渲染器输出
+        true,
+        None,
+    )
+    .await
+    .map_err(exit_on_budget_exceeded)?;
+
+    let command = shell::extract_command(&response.content);
+    if command.is_empty() {
+        eprintln!("{}", "❌Model returned an empty command.".red());
+        exit(1);
+    }
+    let rules = runtime_config.command_rules();
+    // 没有终端可供确认时只输出命令, 命中拒绝规则的命令不输出, 避免被 shell 集成直接填入命令行
+    if print_only || !io::stdin().is_terminal() {
+        if let Some(rule) = shell::check_command(&command, &rules) {
+            shell::print_risk(rule);
+            if rule.risk == CommandRisk::Deny {
+                exit(1);
+            }
+        }
+        println!("{}", command);
+        return Ok(());
+    }
+    handle_command_action(&env, command, &rules)
+}
+
+/// 询问如何处理命令, 直到执行、复制或放弃; 执行时以命令的退出码退出
+fn handle_command_action(
+    env: &ShellEnv,
+    mut command: String,
+    rules: &[CommandRule],
+) -> Result<(), Box<dyn std::error::Error>> {
+    loop {
+        shell::print_command(&command);
+        let rule = shell::check_command(&command, rules);
+        if let Some(rule) = rule {
+            shell::print_risk(rule);
+        }
+        match shell::ask_action()? {
+            CommandAction::Execute => match rule.map(|rule| rule.risk) {
+                Some(CommandRisk::Deny) => {
+                    eprintln!("{}", "❌Refused to execute, edit or copy the command instead.".red());
+                }
+                Some(CommandRisk::Confirm) if !shell::confirm_dangerous()? => {
+                    println!("{}", "Aborted.".yellow());
+                    return Ok(());
+                }
+                _ => exit(shell::run_in_shell(env, &command)?),
+            },
+            CommandAction::Edit => match shell::edit_command(&command) {
+                Ok(edited) => command = edited,
+                Err(e) => eprintln!("{}", format!("❌Failed to edit command: {}", e).red()),
+            },
+            CommandAction::Copy => {
+                shell::copy_to_clipboard(&command)?;
+                println!("{}", "Command copied to clipboard.".green());
+                return Ok(());
+            }
+            CommandAction::Abort => {
+                println!("{}", "Aborted.".yellow());
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// 解释命令失败的原因并给出修正命令, 未指定命令时使用 shell 历史中的最后一条
+///
+/// 输出来自管道输入, 或在 `rerun` 时重新执行命令获取。
+async fn handle_fix_command(
+    runtime_config: &Config,
+    command: &[String],
+    piped_output: Option<&str>,
+    rerun: bool,
+    ignore_budget: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let env = ShellEnv::detect();
+    let command = if command.is_empty() {
+        match shell::last_command(&env) {
+            Ok(Some(command)) => command,
+            Ok(None) => {
+                eprintln!(
+                    "{}",
+                    "❌No command found in shell history, pass the command as an argument.".red()
+                );
+                exit(1);
+            }
+            Err(e) => {
+                let file = shell::history_file(&env).unwrap_or_default();
+                eprintln!(
+                    "{}",
+                    format!("❌Failed to read shell history '{}': {}", file.display(), e).red()
+                );
+                exit(1);
+            }
+        }
+    } else {
+        command.join(" ")
+    };
+    let rules = runtime_config.command_rules();
+
+    let (output, exit_code) = if rerun {
+        if let Some(rule) = shell::check_command(&command, &rules) {
+            shell::print_risk(rule);
+            let confirmed =
+                rule.risk == CommandRisk::Confirm && io::stdin().is_terminal() && shell::confirm_dangerous()?;
+            if !confirmed {
+                eprintln!("{}", "❌Refused to re-run the command.".red());
+                exit(1);
+            }
+        }
+        println!("{} {}", "Re-running:".dark_grey(), command.as_str().cyan());
+        let (output, code) = shell::capture_output(&env, &command)?;
+        (Some(output), Some(code))
+    } else {
+        (piped_output.map(str::to_string), None)
+    };
+    let output = output.map(|output| shell::tail_truncate(&output, shell::MAX_OUTPUT_BYTES));
+    log_debug!("Fix command for {} on {}: {}", env.shell_name(), env.os, command);
+
+    let (model_name, model_config) = resolve_model_config(runtime_config);
+    let prompt_config = PromptConfig {
+        content: shell::fix_prompt(&env),
+    };
+    let message = shell::fix_message(&command, output.as_deref(), exit_code);
+    let response = chat::completion(
+        &[ChatMessage::user(message)],
+        model_name.to_string(),
+        model_config,
+        shell::FIX_PROMPT_NAME.to_string(),
+        &prompt_config,
+        runtime_config,
+        ignore_budget,
+        false,
+        None,
+    )
+    .await
+    .map_err(exit_on_budget_exceeded)?;
+
+    // 输出已由渲染器显示, 没有终端时不再询问
+    match shell::extract_last_code_block(&response.content) {
+        Some(fixed) if io::stdin().is_terminal() => handle_command_action(&env, fixed, &rules),
+        _ => Ok(()),
+    }
+}
+
+/// 执行命令并捕获输出和退出码, 连同问题一起发送给模型, 最后以命令的退出码退出
+///
+/// 对话会被保存, 可以用 `-c` 继续追问。
+async fn handle_run_command(
+    runtime_config: &Config,
+    command: &[String],
+    input: Option<&str>,
+    question: Option<&str>,
+    ignore_budget: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if command.is_empty() {
+        eprintln!("{}", "❌No command to run.".red());
+        exit(1);
+    }
+    let run = match shell::run_and_capture(command, input) {
+        Ok(run) => run,
+        Err(e) => {
+            eprintln!("{}", format!("❌Failed to run '{}': {}", command[0], e).red());
+            exit(if e.kind() == io::ErrorKind::NotFound { 127 } else { 126 });
+        }
+    };
+    let status = format!("exit status: {}", run.exit_code);
+    if runtime_config.pure {
+        println!("{}", status);
+    } else if run.exit_code == 0 {
+        println!("{}", status.dark_grey());
+    } else {
+        println!("{}", status.red());
+    }
+
+    let conversation_manager = ConversationManager::new(&ConfigManager::get_config_dir()?)?;
+    let (model_name, model_config, prompt_name, prompt_config) = resolve_chat_configs(runtime_config);
+    let mut conversation = Conversation::new(model_name, prompt_name);
+    log_debug!(
+        "Explain command `{}` with exit status {}",
+        command.join(" "),
+        run.exit_code
+    );
+    conversation.push(ChatMessage::user(shell::run_message(command, &run, question)), None);
+    let response = chat::completion(
+        &conversation.chat_messages(),
+        model_name.to_string(),
+        model_config,
+        prompt_name.to_string(),
+        prompt_config,
+        runtime_config,
+        ignore_budget,
+        false,
+        None,
+    )
+    .await
+    .map_err(exit_on_budget_exceeded)?;
+    conversation.push(ChatMessage::assistant(response.content), response.usage);
+    conversation_manager.save(&conversation)?;
+
+    if run.exit_code != 0 {
+        exit(run.exit_code);
+    }
+    Ok(())
+}
+
+async fn handle_chat_command(runtime_config: &Config, cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let conversation_manager = ConversationManager::new(&ConfigManager::get_config_dir()?)?;
+    let continued = if cli.continue_conversation {
+        let latest = conversation_manager.latest()?;
+        if latest.is_none() {
+            eprintln!("{}", "⚠ No conversation to continue, starting a new one.".yellow());
+        }
+        latest
     } else {
-        let input = cli.input.join(" ");
-        input
+        None
     };
+    chat_in_conversation(runtime_config, cli, continued, &conversation_manager).await
+}
+
+/// 读取 `-f` 指定的文件和 `--image` 指定的图片, 读取失败时退出
+fn load_attachments(patterns: &[String], images: &[PathBuf]) -> Option<MessageAttachments> {
+    let mut attachments = MessageAttachments::default();
+    if !patterns.is_empty() {
+        let files = FileAttachments::load(patterns).unwrap_or_else(|e| {
+            eprintln!("{}", format!("❌Failed to attach files: {}", e).red());
+            exit(1);
+        });
+        for (path, reason) in &files.skipped {
+            eprintln!("{}", format!("⚠ Skipped {}: {}.", path.display(), reason).yellow());
+        }
+        for file in &files.files {
+            log_debug!("Attach {} ({} bytes)", file.path.display(), file.content.len());
+        }
+        attachments.text = files.to_message();
+    }
+    for path in images {
+        let image = attachment::load_image(path).unwrap_or_else(|e| {
+            eprintln!(
+                "{}",
+                format!("❌Failed to attach image {}: {}", path.display(), e).red()
+            );
+            exit(1);
+        });
+        log_debug!("Attach image {} ({})", path.display(), image.mime_type);
+        attachments.images.push(image);
+    }
+    (!attachments.is_empty()).then_some(attachments)
+}
+
+/// 在已有对话(或新对话)中聊天, 没有输入时进入多轮对话模式
+///
+/// 继续已有对话且未通过 CLI 指定模型或提示时, 沿用该对话的配置。
+async fn chat_in_conversation(
+    runtime_config: &Config,
+    cli: &Cli,
+    conversation: Option<Conversation>,
+    conversation_manager: &ConversationManager,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut runtime_config = runtime_config.clone();
+    if let Some(conversation) = &conversation {
+        log_debug!("Continue conversation {}.", conversation.id);
+        if cli.model.is_none() {
+            runtime_config.default_model = Some(conversation.model_config_name.clone());
+        }
+        if cli.prompt.is_none() {
+            runtime_config.default_prompt = Some(conversation.prompt_config_name.clone());
+        }
+    }
+    let runtime_config = &runtime_config;
+
+    let (model_name, model_config, prompt_name, prompt_config) = resolve_chat_configs(runtime_config);
+    let mut conversation = conversation.unwrap_or_else(|| Conversation::new(model_name, prompt_name));
+    let attachments = load_attachments(&cli.files, &cli.images);
+    let schema = cli.schema.as_deref().map(load_schema);
+
+    // If input is empty, enter multi-turn interactive mode
+    if cli.input.is_empty() {
+        if schema.is_some() {
+            eprintln!("{}", "❌--schema requires an input message.".red());
+            exit(1);
+        }
+        return run_repl(
+            runtime_config,
+            model_name,
+            model_config,
+            prompt_name,
+            prompt_config,
+            conversation,
+            conversation_manager,
+            attachments,
+            cli.ignore_budget,
+        )
+        .await;
+    }
+
+    let input = cli.input.join(" ");
 
     if input.trim().is_empty() {
         println!("{}", "⚠ Input message is empty.".yellow());
@@ -252,18 +908,142 @@ async fn handle_chat_command(runtime_config: &Config, cli: &Cli) -> Result<(), B
         prompt_name,
         &input.safe_substring(20)
     );
-    chat::completion(
-        &input,
+    let message = match &attachments {
+        Some(attachments) => attachments.user_message(&input),
+        None => ChatMessage::user(input),
+    };
+    conversation.push(message, None);
+    conversation.model_config_name = model_name.to_string();
+    conversation.prompt_config_name = prompt_name.to_string();
+    if let Some(schema) = &schema {
+        return chat_with_schema(
+            schema,
+            conversation,
+            model_name,
+            model_config,
+            prompt_name,
+            prompt_config,
+            runtime_config,
+            conversation_manager,
+            cli.ignore_budget,
+            !cli.no_schema_retry,
+        )
+        .await;
+    }
+    let response = chat::completion(
+        &conversation.chat_messages(),
         model_name.to_string(),
-        &model_config,
+        model_config,
         prompt_name.to_string(),
-        &prompt_config,
-        runtime_config.pure,
-        runtime_config.disable_stream,
-        runtime_config.verbose,
+        prompt_config,
+        runtime_config,
+        cli.ignore_budget,
+        false,
+        None,
     )
-    .await?;
+    .await
+    .map_err(exit_on_budget_exceeded)?;
+
+    conversation.push(ChatMessage::assistant(response.content), response.usage);
+    conversation_manager.save(&conversation)?;
 
     log_debug!("Chat Done.");
     Ok(())
 }
+
+/// 读取 `--schema` 指定的文件, 失败时提示并退出
+fn load_schema(path: &std::path::Path) -> JsonSchema {
+    JsonSchema::load(path).unwrap_or_else(|e| {
+        eprintln!("{}", format!("❌Failed to load schema {}: {}", path.display(), e).red());
+        exit(1);
+    })
+}
+
+/// 按 JSON Schema 请求结构化输出, 只输出校验通过的 JSON
+///
+/// 接口以 400 拒绝 JSON Schema 时退回 JSON 模式; `retry` 为 true 时, 回复校验失败会附上错误重试一次。
+#[allow(clippy::too_many_arguments)]
+async fn chat_with_schema(
+    schema: &JsonSchema,
+    mut conversation: Conversation,
+    model_name: &str,
+    model_config: &ModelConfig,
+    prompt_name: &str,
+    prompt_config: &PromptConfig,
+    runtime_config: &Config,
+    conversation_manager: &ConversationManager,
+    ignore_budget: bool,
+    mut retry: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let prompt_config = PromptConfig {
+        content: format!("{}\n\n{}", prompt_config.content, schema.instruction())
+            .trim_start()
+            .to_string(),
+    };
+    let mut response_format = schema.response_format();
+
+    loop {
+        let result = chat::completion(
+            &conversation.chat_messages(),
+            model_name.to_string(),
+            model_config,
+            prompt_name.to_string(),
+            &prompt_config,
+            runtime_config,
+            ignore_budget,
+            true,
+            Some(&response_format),
+        )
+        .await;
+        let response = match result {
+            // 只有接口明确拒绝 JSON Schema 时才退回, 其他错误原样返回, 避免重复计费的请求
+            Err(e)
+                if e.is::<ResponseFormatRejected>() && matches!(response_format, ResponseFormat::JsonSchema { .. }) =>
+            {
+                eprintln!(
+                    "{}",
+                    format!(
+                        "⚠ JSON Schema output is not supported ({}), falling back to JSON mode.",
+                        e
+                    )
+                    .yellow()
+                );
+                response_format = ResponseFormat::JsonObject;
+                continue;
+            }
+            result => result.map_err(exit_on_budget_exceeded)?,
+        };
+        let parsed = schema.parse_reply(&response.content);
+        conversation.push(ChatMessage::assistant(response.content), response.usage);
+        match parsed {
+            Ok(value) => {
+                conversation_manager.save(&conversation)?;
+                // 终端中格式化显示, 管道中输出单行便于脚本处理
+                let json = if io::stdout().is_terminal() {
+                    serde_json::to_string_pretty(&value)?
+                } else {
+                    serde_json::to_string(&value)?
+                };
+                println!("{}", json);
+                return Ok(());
+            }
+            Err(mismatch) if retry => {
+                retry = false;
+                eprintln!("{}", "⚠ Reply does not match the schema, retrying once.".yellow());
+                log_debug!("Schema mismatch: {}", mismatch);
+                conversation.push(
+                    ChatMessage::user(format!(
+                        "{}\n\nRespond again with only the corrected JSON that conforms to the schema.",
+                        mismatch
+                    )),
+                    None,
+                );
+            }
+            Err(mismatch) => {
+                conversation_manager.save(&conversation)?;
+                eprintln!("{}", format!("❌{}", mismatch).red());
+                exit(SCHEMA_MISMATCH_EXIT_CODE);
+            }
+        }
+    }
+}
@@ -1,15 +1,22 @@
 use std::io::{self, Write};
 
-pub async fn interactive_input() -> Result<String, Box<dyn std::error::Error>> {
-    println!("Enter your message (Enter empty lines to send):");
-
+/// 读取一条多行消息, 以空行结束
+///
+/// 读到 EOF(Ctrl+D) 且没有任何输入时返回 `None`。
+pub fn read_message() -> io::Result<Option<String>> {
     let mut lines = Vec::new();
 
     loop {
         io::stdout().flush()?;
 
         let mut line = String::new();
-        io::stdin().read_line(&mut line)?;
+        if io::stdin().read_line(&mut line)? == 0 {
+            // EOF
+            if lines.is_empty() {
+                return Ok(None);
+            }
+            break;
+        }
 
         let line = line.trim().to_string();
 
@@ -26,5 +33,5 @@ pub async fn interactive_input() -> Result<String, Box<dyn std::error::Error>> {
         }
     }
 
-    Ok(lines.join("\n"))
+    Ok(Some(lines.join("\n")))
 }
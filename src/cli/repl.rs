@@ -0,0 +1,95 @@
+use crossterm::style::Stylize;
+
+use crate::attachment::MessageAttachments;
+use crate::chat::{self, ChatMessage};
+use crate::cli::interactive::read_message;
+use crate::config::{Config, ModelConfig, PromptConfig};
+use crate::conversation::{Conversation, ConversationManager};
+use crate::log_debug;
+
+/// 多轮对话模式, 保留完整的消息历史, 直到 `/exit` 或 Ctrl+D 退出
+///
+/// 每轮成功的对话都会追加到 `conversation` 并保存, `attachments` 附加到第一条消息。
+#[allow(clippy::too_many_arguments)]
+pub async fn run_repl(
+    runtime_config: &Config,
+    model_config_name: &str,
+    model_config: &ModelConfig,
+    prompt_config_name: &str,
+    prompt_config: &PromptConfig,
+    mut conversation: Conversation,
+    conversation_manager: &ConversationManager,
+    mut attachments: Option<MessageAttachments>,
+    ignore_budget: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!(
+        "{}",
+        "Multi-turn chat. Enter an empty line to send, /exit or Ctrl+D to quit.".dark_grey()
+    );
+    if !conversation.messages.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "Continue conversation {} ({} messages).",
+                conversation.id,
+                conversation.messages.len()
+            )
+            .dark_grey()
+        );
+    }
+
+    loop {
+        print!("{} ", ">>>".green().bold());
+        let Some(input) = read_message()? else {
+            println!();
+            break;
+        };
+        let input = input.trim();
+        if input == "/exit" {
+            break;
+        }
+        if input.is_empty() {
+            continue;
+        }
+
+        // 附件附加到第一条成功发送的消息
+        let message = match &attachments {
+            Some(attachments) => attachments.user_message(input),
+            None => ChatMessage::user(input),
+        };
+        conversation.push(message, None);
+        let result = chat::completion(
+            &conversation.chat_messages(),
+            model_config_name.to_string(),
+            model_config,
+            prompt_config_name.to_string(),
+            prompt_config,
+            runtime_config,
+            ignore_budget,
+            false,
+            None,
+        )
+        .await;
+
+        match result {
+            Ok(response) => {
+                conversation.model_config_name = model_config_name.to_string();
+                conversation.prompt_config_name = prompt_config_name.to_string();
+                conversation.push(ChatMessage::assistant(response.content), response.usage);
+                conversation_manager.save(&conversation)?;
+                attachments = None;
+                if runtime_config.pure {
+                    println!();
+                }
+            }
+            Err(e) => {
+                // 失败的轮次不计入历史, 便于重试
+                conversation.messages.pop();
+                eprintln!("{}", format!("❌{}", e).red());
+            }
+        }
+        log_debug!("REPL turn done, {} messages in history.", conversation.messages.len());
+    }
+
+    Ok(())
+}
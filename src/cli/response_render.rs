@@ -1,6 +1,8 @@
-use crossterm::
-    style::Stylize
-;
+use crossterm::{
+    execute,
+    style::Stylize,
+    terminal::{Clear, ClearType},
+};
 use std::{
     io::{Write, stdout},
     path::is_separator,
@@ -11,7 +13,11 @@ use tokio::{
     time::sleep,
 };
 
-use crate::{log_debug, log_trace};
+use crate::{
+    chat::{ChatResponse, FinishReason},
+    log_debug, log_trace,
+    utils::format_cost,
+};
 
 /// 渲染配置
 #[derive(Clone)]
@@ -23,6 +29,18 @@ pub struct RenderConfig {
     /// 打字机效果的速度（字符/秒）
     pub type_speed: u32,
     pub disable_stream: bool,
+    /// 推理过程折叠为一行
+    pub hide_reasoning: bool,
+    /// 不输出任何内容
+    pub quiet: bool,
+}
+
+/// 发送给渲染器的内容
+pub enum RenderMessage {
+    /// 推理过程
+    Reasoning(String),
+    /// 回复内容
+    Content(String),
 }
 
 /// 响应渲染器
@@ -38,41 +56,95 @@ impl ResponseRenderer {
         }
     }
 
-    pub fn start_render(&self, config: RenderConfig) -> (Sender<String>, tokio::task::JoinHandle<()>) {
+    pub fn start_render(&self, config: RenderConfig) -> (Sender<RenderMessage>, tokio::task::JoinHandle<()>) {
         let (message_tx, message_rx) = mpsc::channel(100);
 
         // 仅启动一个渲染任务，处理所有消息
         let render_handler = tokio::spawn(async move {
             Self::render_task_impl(config, message_rx).await;
         });
-        return (message_tx, render_handler);
+        (message_tx, render_handler)
     }
 
     /// 渲染任务 - 处理所有消息并渲染
-    async fn render_task_impl(config: RenderConfig, mut message_rx: Receiver<String>) {
+    async fn render_task_impl(config: RenderConfig, mut message_rx: Receiver<RenderMessage>) {
         let mut stdout = stdout();
+        if config.quiet {
+            while message_rx.recv().await.is_some() {}
+            return;
+        }
         if !config.pure {
-            let _ = Self::render_status_bar(&config);
+            Self::render_status_bar(&config);
             log_trace!("Render Status Bar.");
         }
 
         let char_interval: Duration = Duration::from_secs_f32(1.0 / config.type_speed as f32);
 
+        // 思考时长从请求开始计算, 非流式请求时推理过程是一次性收到的
+        let request_start = Instant::now();
+        let mut in_reasoning = false;
+
         // 非阻塞处理所有待处理消息
-        while let Some(value) = message_rx.recv().await {
-            //渲染字符
-            if config.disable_stream {
-                print!("{}", value);
-            } else {
-                Self::print_with_interval(value.as_str(), char_interval).await;
+        while let Some(message) = message_rx.recv().await {
+            match message {
+                // 纯净模式不输出推理过程
+                RenderMessage::Reasoning(_) if config.pure => {}
+                RenderMessage::Reasoning(value) => {
+                    if !in_reasoning {
+                        in_reasoning = true;
+                        Self::render_reasoning_header(&config);
+                    }
+                    // 推理过程可能很长, 不使用打字机效果
+                    if !config.hide_reasoning {
+                        print!("{}", value.dim());
+                        let _ = stdout.flush();
+                    }
+                }
+                RenderMessage::Content(value) => {
+                    if in_reasoning {
+                        in_reasoning = false;
+                        Self::render_reasoning_footer(&config, request_start);
+                    }
+                    //渲染字符
+                    if config.disable_stream {
+                        print!("{}", value);
+                    } else {
+                        Self::print_with_interval(value.as_str(), char_interval).await;
+                    }
+                }
             }
         }
+        if in_reasoning {
+            Self::render_reasoning_footer(&config, request_start);
+        }
 
         log_debug!("Message Receiver Exit.");
         // 结束时换行
         let _ = stdout.flush();
     }
 
+    /// 推理块的开头, 折叠时只占一行
+    fn render_reasoning_header(config: &RenderConfig) {
+        if config.hide_reasoning {
+            print!("{}", "💭 thinking...".dim());
+        } else {
+            println!("{}", "💭 thinking".dim());
+        }
+        let _ = stdout().flush();
+    }
+
+    /// 推理块的结尾, 折叠时覆盖开头那一行
+    fn render_reasoning_footer(config: &RenderConfig, start: Instant) {
+        let summary = format!("💭 thought for {:.1}s", start.elapsed().as_secs_f64());
+        if config.hide_reasoning {
+            print!("\r");
+            let _ = execute!(stdout(), Clear(ClearType::CurrentLine));
+        } else {
+            println!();
+        }
+        println!("{}\n", summary.dim());
+    }
+
     /// 渲染状态栏（固定在status_row）
     fn render_status_bar(config: &RenderConfig) {
         println!(
@@ -86,9 +158,32 @@ impl ResponseRenderer {
             "".on_dark_green()
         );
     }
-    pub fn render_tail_bar(&self) {
+    pub fn render_tail_bar(&self, response: &ChatResponse) {
         let cost = Instant::now() - self.start_time;
-        println!("\n✅{}\n", format!("{:#?}", cost).dark_green());
+        let mut parts = vec![format!("{:#?}", cost).dark_green().to_string()];
+        if let Some(ttft) = response.time_to_first_token {
+            parts.push(format!("{} {:.2}s", "ttft".dark_grey(), ttft.as_secs_f64()));
+        }
+        if let Some(usage) = response.usage {
+            parts.push(format!(
+                "{} {} + {} = {}",
+                "tokens".dark_grey(),
+                usage.prompt_tokens,
+                usage.completion_tokens,
+                usage.total_tokens
+            ));
+        }
+        if let Some(tps) = response.tokens_per_second() {
+            parts.push(format!("{:.1} {}", tps, "tok/s".dark_grey()));
+        }
+        if let Some(cost) = response.cost {
+            parts.push(format!("{} {}", "cost".dark_grey(), format_cost(cost)));
+        }
+        // 非正常结束时提示原因, 如被 max tokens 截断
+        if let Some(reason) = response.finish_reason.as_ref().filter(|r| **r != FinishReason::Stop) {
+            parts.push(format!("{} {}", "finish".dark_grey(), reason.to_string().yellow()));
+        }
+        println!("\n✅{}\n", parts.join("    "));
     }
     /// 异步函数：按给定时间间隔打印字符串的每个单词
     async fn print_with_interval(s: &str, word_interval: Duration) {
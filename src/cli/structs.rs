@@ -1,4 +1,10 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+use crate::chat::DEFAULT_OLLAMA_BASE_URL;
+use crate::config::{BudgetAction, ModelConfig, OpenAIApi, ReasoningEffort};
+use crate::ledger::StatsPeriod;
+use crate::shell::ShellKind;
 
 #[derive(Parser)]
 #[command(
@@ -8,8 +14,8 @@ use clap::{Parser, Subcommand};
 A terminal AI/LLM chat tool
 
 aichat [MESSAGE]   # directly chat 
-aichat [COMMAND] [ARGS]     # setting or view configs"#,
-    arg_required_else_help = true
+aichat             # multi-turn chat
+aichat [COMMAND] [ARGS]     # setting or view configs"#
 )]
 pub struct Cli {
     #[command(subcommand)]
@@ -35,6 +41,38 @@ pub struct Cli {
     #[arg(long)]
     pub disable_stream: bool,
 
+    /// Collapse the reasoning (thinking) of reasoning models into one line
+    #[arg(long)]
+    pub hide_reasoning: bool,
+
+    /// Send the request even if a usage budget is exceeded
+    #[arg(long)]
+    pub ignore_budget: bool,
+
+    /// Override sampling parameters of the model for this invocation
+    #[command(flatten)]
+    pub sampling: SamplingArgs,
+
+    /// Continue the most recent conversation
+    #[arg(short = 'c', long = "continue")]
+    pub continue_conversation: bool,
+
+    /// Attach a file, directory or quoted glob to the message (repeatable)
+    #[arg(short = 'f', long = "file", value_name = "PATH")]
+    pub files: Vec<String>,
+
+    /// Attach a PNG, JPEG or WebP image to the message (repeatable)
+    #[arg(long = "image", value_name = "PATH")]
+    pub images: Vec<PathBuf>,
+
+    /// Request JSON output matching a JSON Schema file and print only the validated JSON
+    #[arg(long = "schema", value_name = "FILE")]
+    pub schema: Option<PathBuf>,
+
+    /// Do not retry when the reply does not match --schema
+    #[arg(long = "no-schema-retry", requires = "schema")]
+    pub no_schema_retry: bool,
+
     /// Specify config file path
     // #[arg(long)]
     // pub config: Option<String>,
@@ -47,8 +85,11 @@ pub struct Cli {
 }
 
 #[derive(Subcommand)]
-
+#[allow(clippy::large_enum_variant)]
 pub enum Commands {
+    /// Start a multi-turn interactive chat
+    Chat,
+
     /// Set model or prompt configuration
     Set {
         #[command(subcommand)]
@@ -69,22 +110,92 @@ pub enum Commands {
 
     /// List configurations
     List {
-        /// Type of configuration to list (models/prompts/all)
+        /// Type of configuration to list (models/prompts/budgets/rules/all)
         #[arg(default_value = "all")]
         config_type: String,
+        /// Show details of one model configuration
+        name: Option<String>,
+    },
+
+    /// Show usage statistics from the local ledger
+    Stats {
+        /// Aggregation period
+        #[arg(long, value_enum, default_value = "day")]
+        period: StatsPeriod,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Manage saved conversations
+    Session {
+        #[command(subcommand)]
+        command: SessionCommands,
+    },
+
+    /// Discover and import models
+    Models {
+        #[command(subcommand)]
+        command: ModelsCommands,
+    },
+
+    /// Generate a shell command, then execute, edit or copy it
+    Cmd {
+        /// Only print the command, without asking what to do with it
+        #[arg(long)]
+        print: bool,
+        /// What the command should do
+        #[arg(required = true)]
+        input: Vec<String>,
+    },
+
+    /// Explain why a command failed and suggest a fix, defaults to the last command in shell history
+    Fix {
+        /// Re-run the command to capture its output and exit status
+        #[arg(long)]
+        rerun: bool,
+        /// The failed command, its output can be piped in
+        command: Vec<String>,
+    },
+
+    /// Run a command, then ask the model to explain its output or failure
+    #[command(trailing_var_arg = true)]
+    Run {
+        /// What to ask about the output, defaults to explaining the failure or the output
+        #[arg(short = 'q', long)]
+        question: Option<String>,
+        /// The command and its arguments, e.g. `aichat run -- cargo build`
+        #[arg(required = true, allow_hyphen_values = true)]
+        command: Vec<String>,
     },
+
+    /// Print a key binding (Ctrl+G) that turns the command line into a suggested command
+    ShellInit { shell: ShellKind },
 }
 
 #[derive(Subcommand)]
 #[command(
     arg_required_else_help = true   // 👈 只对 Add 生效
 )]
+#[allow(clippy::large_enum_variant)]
 pub enum SetCommands {
     /// Set model configuration
     Model {
         /// Name of the model configuration
         #[arg(index = 1, value_parser = non_empty_string)]
         name: String,
+        /// API provider
+        #[arg(long, value_enum)]
+        provider: Option<ProviderKind>,
+        /// API used by OpenAI/Azure providers
+        #[arg(long, value_enum)]
+        api: Option<OpenAIApi>,
+        /// Azure deployment id (defaults to the model name)
+        #[arg(long, value_parser = non_empty_string)]
+        deployment_id: Option<String>,
+        /// Azure api-version
+        #[arg(long, value_parser = non_empty_string)]
+        api_version: Option<String>,
         /// Base URL for API
         #[arg(long, value_parser = non_empty_string)]
         base_url: Option<String>,
@@ -95,8 +206,20 @@ pub enum SetCommands {
         #[arg(long)]
         api_key: Option<String>,
 
+        #[command(flatten)]
+        sampling: SamplingArgs,
+        /// Extra JSON fields deep-merged into the request body, inline or `@path/to/file.json`
+        #[arg(long, value_parser = json_object)]
+        extra_body: Option<serde_json::Value>,
+        /// Custom HTTP header `Name: value`, repeatable; `${ENV}` is expanded per request, empty value removes it
+        #[arg(long = "header", value_name = "NAME: VALUE", value_parser = header_pair)]
+        headers: Vec<(String, String)>,
+        /// Input price per million tokens, used to estimate cost
+        #[arg(long)]
+        input_price_per_mtok: Option<f64>,
+        /// Output price per million tokens, used to estimate cost
         #[arg(long)]
-        temperature: Option<f32>,
+        output_price_per_mtok: Option<f64>,
     },
     /// Set prompt configuration
     Prompt {
@@ -107,6 +230,81 @@ pub enum SetCommands {
         #[arg(long)]
         content: String,
     },
+    /// Set usage budget (global, or for one model with --model)
+    Budget {
+        /// Name of the model configuration, omit to set the global budget
+        #[arg(long, value_parser = non_empty_string)]
+        model: Option<String>,
+        /// Max total tokens per day
+        #[arg(long)]
+        daily_tokens: Option<u64>,
+        /// Max total tokens per month
+        #[arg(long)]
+        monthly_tokens: Option<u64>,
+        /// Max estimated cost per day
+        #[arg(long)]
+        daily_cost: Option<f64>,
+        /// Max estimated cost per month
+        #[arg(long)]
+        monthly_cost: Option<f64>,
+        /// What to do when the budget is exceeded
+        #[arg(long, value_enum)]
+        action: Option<BudgetAction>,
+    },
+}
+
+/// 采样参数, 用于 `aichat set model` 及单次调用时覆盖模型配置
+#[derive(Args, Clone, Default)]
+pub struct SamplingArgs {
+    /// Sampling temperature
+    #[arg(long)]
+    pub temperature: Option<f32>,
+    /// Max tokens to generate (legacy `max_tokens`)
+    #[arg(long)]
+    pub max_tokens: Option<u32>,
+    /// Max tokens to generate, including reasoning tokens
+    #[arg(long)]
+    pub max_completion_tokens: Option<u32>,
+    /// Nucleus sampling probability mass
+    #[arg(long)]
+    pub top_p: Option<f32>,
+    /// Frequency penalty (-2.0 ~ 2.0)
+    #[arg(long, allow_hyphen_values = true)]
+    pub frequency_penalty: Option<f32>,
+    /// Presence penalty (-2.0 ~ 2.0)
+    #[arg(long, allow_hyphen_values = true)]
+    pub presence_penalty: Option<f32>,
+    /// Stop sequence (repeatable, up to 4)
+    #[arg(long)]
+    pub stop: Vec<String>,
+    /// Seed for deterministic sampling
+    #[arg(long, allow_hyphen_values = true)]
+    pub seed: Option<i64>,
+    /// Number of choices to generate (only the first one is shown)
+    #[arg(long)]
+    pub n: Option<u8>,
+    /// Reasoning effort for reasoning models
+    #[arg(long, value_enum)]
+    pub reasoning_effort: Option<ReasoningEffort>,
+}
+
+impl SamplingArgs {
+    /// 转为只包含采样参数的模型配置, 用于合并
+    pub fn to_model_config(&self) -> ModelConfig {
+        ModelConfig {
+            temperature: self.temperature,
+            max_tokens: self.max_tokens,
+            max_completion_tokens: self.max_completion_tokens,
+            top_p: self.top_p,
+            frequency_penalty: self.frequency_penalty,
+            presence_penalty: self.presence_penalty,
+            stop: (!self.stop.is_empty()).then(|| self.stop.clone()),
+            seed: self.seed,
+            n: self.n,
+            reasoning_effort: self.reasoning_effort,
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -135,6 +333,54 @@ pub enum DeleteCommands {
         /// Name of the prompt configuration
         name: String,
     },
+    /// Delete usage budget (global, or for one model with --model)
+    Budget {
+        /// Name of the model configuration, omit to delete the global budget
+        #[arg(long)]
+        model: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SessionCommands {
+    /// List saved conversations
+    List,
+    /// Show all messages of a conversation
+    Show {
+        /// Conversation id (or a unique prefix of it)
+        id: String,
+    },
+    /// Resume a conversation in multi-turn chat mode
+    Resume {
+        /// Conversation id (or a unique prefix of it)
+        id: String,
+    },
+    /// Rename a conversation
+    Rename {
+        /// Conversation id (or a unique prefix of it)
+        id: String,
+        /// New title
+        #[arg(value_parser = non_empty_string)]
+        title: String,
+    },
+    /// Delete a conversation
+    Delete {
+        /// Conversation id (or a unique prefix of it)
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ModelsCommands {
+    /// Create a model configuration for every model of a local Ollama server
+    ImportOllama {
+        /// Ollama server address
+        #[arg(long, default_value = DEFAULT_OLLAMA_BASE_URL, value_parser = non_empty_string)]
+        base_url: String,
+        /// Prefix of the created model configuration names
+        #[arg(long, default_value = "ollama-")]
+        prefix: String,
+    },
 }
 
 fn non_empty_string(s: &str) -> Result<String, String> {
@@ -144,3 +390,40 @@ fn non_empty_string(s: &str) -> Result<String, String> {
         Ok(s.to_string())
     }
 }
+
+/// `set model --provider` 可选的接口类型
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ProviderKind {
+    /// OpenAI compatible `/chat/completions`
+    #[value(name = "openai")]
+    OpenAI,
+    /// Azure OpenAI, base URL is the resource endpoint
+    Azure,
+    /// Anthropic Messages API
+    Anthropic,
+    /// Google Gemini API
+    Gemini,
+    /// Ollama native `/api/chat`
+    Ollama,
+}
+
+/// 解析 `Name: value` 形式的请求头参数
+fn header_pair(s: &str) -> Result<(String, String), String> {
+    let (name, value) = s.split_once(':').ok_or("expected `Name: value`")?;
+    let name = name.trim();
+    reqwest::header::HeaderName::from_bytes(name.as_bytes()).map_err(|e| format!("invalid header name: {}", e))?;
+    Ok((name.to_string(), value.trim().to_string()))
+}
+
+/// 解析 JSON 对象参数, 以 `@` 开头时从文件读取
+fn json_object(s: &str) -> Result<serde_json::Value, String> {
+    let content = match s.strip_prefix('@') {
+        Some(path) => std::fs::read_to_string(path).map_err(|e| format!("cannot read '{}': {}", path, e))?,
+        None => s.to_string(),
+    };
+    let value: serde_json::Value = serde_json::from_str(&content).map_err(|e| format!("invalid JSON: {}", e))?;
+    if !value.is_object() {
+        return Err("must be a JSON object".to_string());
+    }
+    Ok(value)
+}
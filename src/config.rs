@@ -2,9 +2,11 @@ mod builder;
 mod display;
 mod manager;
 mod resolver;
+mod rules;
 mod structs;
 pub use builder::*;
 pub use display::*;
 pub use manager::*;
 pub use resolver::*;
+pub use rules::*;
 pub use structs::*;
@@ -67,6 +67,7 @@ impl ConfigBuilder {
                 base_url: Some("https://api.openai.com/v1".to_string()),
                 api_key: None,
                 temperature: None,
+                ..Default::default()
             },
         )
         .set_prompt(
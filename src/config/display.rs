@@ -1,4 +1,5 @@
-use super::{Config, ModelConfig};
+use super::{BudgetAction, BudgetConfig, CommandRisk, Config, ModelConfig};
+use crate::utils::{StringUtilsTrait, format_cost};
 use crossterm::{
     execute,
     style::{Color, Print, ResetColor, SetForegroundColor, Stylize},
@@ -41,6 +42,23 @@ pub fn print_models(config: &Config) -> io::Result<()> {
         "└─────────────────────┴─────────────────────┴─────────────────────────────────────┴─────────────────┘"
     )?;
 
+    // 接口类型、采样参数与自定义请求头
+    for (name, model) in &config.models {
+        if let Some(provider) = &model.provider {
+            println!("{}: provider {}", name.clone().blue(), provider);
+        }
+        if let Some(api) = model.api {
+            println!("{}: api {}", name.clone().blue(), api);
+        }
+        let summary = model.sampling_summary();
+        if !summary.is_empty() {
+            println!("{}: {}", name.clone().blue(), summary);
+        }
+        if let Some(headers) = format_headers(model) {
+            println!("{}: headers {}", name.clone().blue(), headers);
+        }
+    }
+
     Ok(())
 }
 
@@ -52,7 +70,7 @@ fn print_model_row(stdout: &mut io::Stdout, name: &str, model: &ModelConfig, is_
 
     // 名称
     execute!(stdout, SetForegroundColor(color))?;
-    write!(stdout, "{:<19}", truncate(name, 19))?;
+    write!(stdout, "{:<19}", name.safe_truncate(19))?;
     execute!(stdout, ResetColor)?;
     write!(stdout, " │ ")?;
 
@@ -61,14 +79,15 @@ fn print_model_row(stdout: &mut io::Stdout, name: &str, model: &ModelConfig, is_
     write!(
         stdout,
         "{:<19}",
-        truncate(model.model_name.as_deref().unwrap_or(""), 19)
+        model.model_name.as_deref().unwrap_or("").safe_truncate(19)
     )?;
     execute!(stdout, ResetColor)?;
     write!(stdout, " │ ")?;
 
     // Base URL
     execute!(stdout, SetForegroundColor(color))?;
-    write!(stdout, "{:<35}", truncate(model.base_url.as_deref().unwrap_or(""), 35))?;
+    let base_url = model.base_url.as_deref().unwrap_or("");
+    write!(stdout, "{:<35}", base_url.safe_truncate(35))?;
     execute!(stdout, ResetColor)?;
     write!(stdout, " │ ")?;
 
@@ -82,13 +101,48 @@ fn print_model_row(stdout: &mut io::Stdout, name: &str, model: &ModelConfig, is_
     Ok(())
 }
 
+/// 打印单个模型的完整配置
+pub fn print_model_detail(name: &str, model: &ModelConfig, is_default: bool) {
+    let default_text = if is_default { "(default)".green() } else { "".green() };
+    println!("{}{}", name.blue().bold(), default_text);
+    let field = |label: &str, value: Option<String>| {
+        if let Some(value) = value {
+            println!("  {} {}", format!("{:<14}", label).dark_green(), value);
+        }
+    };
+    field("provider", model.provider.as_ref().map(|p| p.to_string()));
+    field("api", model.api.map(|api| api.to_string()));
+    field("model name", model.model_name.clone());
+    field("base url", model.base_url.clone());
+    field("api key", model.api_key.as_deref().map(mask_api_key));
+    let summary = model.sampling_summary();
+    field("sampling", (!summary.is_empty()).then_some(summary));
+    field(
+        "input price",
+        model.input_price_per_mtok.map(|p| format!("{} / Mtok", p)),
+    );
+    field(
+        "output price",
+        model.output_price_per_mtok.map(|p| format!("{} / Mtok", p)),
+    );
+    field("headers", format_headers(model));
+    field("budget", model.budget.as_ref().map(format_budget));
+    if let Some(extra_body) = &model.extra_body {
+        println!("  {}", "extra body".dark_green());
+        let pretty = serde_json::to_string_pretty(extra_body).unwrap_or_default();
+        for line in pretty.lines() {
+            println!("    {}", line);
+        }
+    }
+}
+
 /// 打印提示列表
 pub fn print_prompts(config: &Config) {
     println!("{}", "Prompts:".on_blue().black());
     // 显示默认模型
     if let Some(default) = &config.default_prompt {
         print!("Default prompt: ");
-        print!("{}\n", default.clone().green());
+        println!("{}", default.clone().green());
     }
     for (name, prompt) in &config.prompts {
         let default_text = if config.default_prompt.as_deref() == Some(name) {
@@ -102,13 +156,75 @@ pub fn print_prompts(config: &Config) {
     }
 }
 
-/// 截断字符串
-fn truncate(s: &str, max_len: usize) -> String {
-    if s.len() > max_len {
-        format!("{}...", &s[..max_len - 3])
-    } else {
-        s.to_string()
+/// 打印预算配置
+pub fn print_budgets(config: &Config) {
+    println!("{}", "Budgets:".on_blue().black());
+    if let Some(budget) = &config.budget {
+        println!("{}: {}", "global".blue().bold(), format_budget(budget));
+    }
+    for (name, model) in &config.models {
+        if let Some(budget) = &model.budget {
+            println!("{}: {}", name.clone().blue().bold(), format_budget(budget));
+        }
+    }
+    println!();
+}
+
+/// 打印生效的危险命令规则
+pub fn print_command_rules(config: &Config) {
+    println!("{}", "Command rules:".on_blue().black());
+    if config.command_rules.is_none() {
+        println!("{}", "(built-in defaults)".dark_grey());
+    }
+    for rule in config.command_rules() {
+        let risk = match rule.risk {
+            CommandRisk::Deny => "deny   ".red(),
+            CommandRisk::Confirm => "confirm".yellow(),
+        };
+        println!("{} {} {}", risk, rule.description, rule.pattern.dark_grey());
+    }
+    println!();
+}
+
+fn format_budget(budget: &BudgetConfig) -> String {
+    let mut parts = Vec::new();
+    if let Some(val) = budget.daily_tokens {
+        parts.push(format!("daily {} tokens", val));
+    }
+    if let Some(val) = budget.monthly_tokens {
+        parts.push(format!("monthly {} tokens", val));
+    }
+    if let Some(val) = budget.daily_cost {
+        parts.push(format!("daily {}", format_cost(val)));
+    }
+    if let Some(val) = budget.monthly_cost {
+        parts.push(format!("monthly {}", format_cost(val)));
     }
+    let action = match budget.action.unwrap_or_default() {
+        BudgetAction::Block => "block",
+        BudgetAction::Warn => "warn",
+    };
+    format!("{}  ({})", parts.join(", "), action)
+}
+
+/// 格式化自定义请求头, 值按 api key 的方式脱敏, 环境变量引用原样显示
+fn format_headers(model: &ModelConfig) -> Option<String> {
+    let headers = model.headers.as_ref().filter(|headers| !headers.is_empty())?;
+    let mut names: Vec<&String> = headers.keys().collect();
+    names.sort();
+    let parts: Vec<String> = names
+        .into_iter()
+        .map(|name| {
+            let value = &headers[name];
+            let value = if value.contains("${") {
+                value.clone()
+            } else {
+                mask_api_key(value)
+            };
+            format!("{}={}", name, value)
+        })
+        .collect();
+    Some(parts.join(", "))
 }
 
 /// 脱敏API key
@@ -117,10 +233,25 @@ fn mask_api_key(key: &str) -> String {
         return String::new();
     }
 
-    let len = key.len();
+    // 按字符计算, 避免切在多字节字符中间
+    let len = key.chars().count();
     if len <= 8 {
         "*".repeat(len)
     } else {
-        format!("{}***{}", &key[..4], &key[len - 4..])
+        let head: String = key.chars().take(4).collect();
+        let tail: String = key.chars().skip(len - 4).collect();
+        format!("{}***{}", head, tail)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_api_key() {
+        assert_eq!(mask_api_key("sk-1234567890"), "sk-1***7890");
+        assert_eq!(mask_api_key("我的应用"), "****");
+        assert_eq!(mask_api_key("我的终端助手应用名称"), "我的终端***应用名称");
     }
 }
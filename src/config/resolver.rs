@@ -6,8 +6,16 @@ use super::Config;
 /// 合并CLI参数和文件配置
 /// 优先级: CLI > 文件配置
 pub fn merge_config(file_config: &Config, cli: &Cli) -> Config {
+    // CLI中的采样参数覆盖所有模型配置
+    let sampling = cli.sampling.to_model_config();
+    let models = file_config
+        .models
+        .iter()
+        .map(|(name, model)| (name.clone(), sampling.clone().merge_with(model)))
+        .collect();
+
     Config {
-        models: file_config.models.clone(),
+        models,
         prompts: file_config.prompts.clone(),
         
         // CLI参数优先
@@ -18,6 +26,10 @@ pub fn merge_config(file_config: &Config, cli: &Cli) -> Config {
         disable_stream: cli.disable_stream || file_config.disable_stream,
         pure: cli.pure || file_config.pure,
         verbose: cli.verbose || file_config.verbose,
+        hide_reasoning: cli.hide_reasoning || file_config.hide_reasoning,
+
+        budget: file_config.budget.clone(),
+        command_rules: file_config.command_rules.clone(),
     }
 }
 
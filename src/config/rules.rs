@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+/// 命中规则时的处理方式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandRisk {
+    /// 需要输入确认后才能执行
+    Confirm,
+    /// 拒绝执行
+    Deny,
+}
+
+/// 危险命令规则, 用于检查模型生成的 shell 命令
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CommandRule {
+    /// 匹配命令的正则表达式
+    pub pattern: String,
+    pub risk: CommandRisk,
+    /// 命中时显示的说明
+    pub description: String,
+}
+
+impl CommandRule {
+    fn new(pattern: &str, risk: CommandRisk, description: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            risk,
+            description: description.to_string(),
+        }
+    }
+}
+
+/// 配置文件中没有 `command-rules` 时使用的规则
+pub fn default_command_rules() -> Vec<CommandRule> {
+    use CommandRisk::{Confirm, Deny};
+    vec![
+        CommandRule::new(
+            r"\brm\s+(?:-\S+\s+)*(?:/|/\*|~/?|~/\*|\$HOME/?)(?:\s|;|&|\||$)",
+            Deny,
+            "deletes the root or home directory",
+        ),
+        CommandRule::new(r"--no-preserve-root", Deny, "disables the root directory safeguard"),
+        CommandRule::new(r":\(\)\s*\{\s*:\s*\|\s*:\s*&\s*\}\s*;\s*:", Deny, "fork bomb"),
+        CommandRule::new(
+            r"\brm\s+(?:-\S+\s+)*-(?:[a-zA-Z]*[rR][a-zA-Z]*f|[a-zA-Z]*f[a-zA-Z]*[rR])",
+            Confirm,
+            "recursively force-deletes files",
+        ),
+        CommandRule::new(r"\bdd\b[^|;&]*\bof=/dev/", Confirm, "writes directly to a device"),
+        CommandRule::new(
+            r">\s*/dev/(?:sd|hd|vd|xvd|nvme|mmcblk|disk)",
+            Confirm,
+            "overwrites a disk device",
+        ),
+        CommandRule::new(r"\bmkfs(?:\.\w+)?\b", Confirm, "formats a filesystem"),
+        CommandRule::new(
+            r"\bchmod\s+(?:(?:-\S*R\S*|--recursive)\s+0?777|0?777\s+(?:-\S*R\S*|--recursive))",
+            Confirm,
+            "recursively makes files world-writable",
+        ),
+        CommandRule::new(
+            r"\b(?:curl|wget)\b[^|;&]*\|\s*(?:sudo\s+)?(?:ba|z|da|k|fi)?sh\b",
+            Confirm,
+            "pipes a downloaded script into a shell",
+        ),
+        CommandRule::new(
+            r"\bgit\s+push\b.*\s(?:--force(?:-with-lease)?|-f)\b",
+            Confirm,
+            "force-pushes and may overwrite remote history",
+        ),
+    ]
+}
@@ -1,12 +1,163 @@
+use super::{CommandRule, default_command_rules};
+use crate::chat::TokenUsage;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct ModelConfig {
+    /// 接口类型, 未设置时为 OpenAI 兼容接口
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<Provider>,
+    /// OpenAI/Azure 使用的接口, 未设置时为 chat completions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api: Option<OpenAIApi>,
     pub model_name: Option<String>,
     pub base_url: Option<String>,
     pub api_key: Option<String>, // This will be encrypted in the config file
     pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_completion_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<ReasoningEffort>,
+    /// 深度合并到请求体中的额外字段(JSON对象)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extra_body: Option<serde_json::Value>,
+    /// 自定义请求头, 值中可用 `${ENV}` 引用环境变量
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headers: Option<HashMap<String, String>>,
+    /// 输入价格(每百万 token)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_price_per_mtok: Option<f64>,
+    /// 输出价格(每百万 token)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_price_per_mtok: Option<f64>,
+    /// 仅对该模型生效的用量预算
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetConfig>,
+}
+
+/// 按 key 合并请求头, 值为空字符串时删除该请求头
+fn merge_headers(
+    headers: Option<HashMap<String, String>>,
+    base: &Option<HashMap<String, String>>,
+) -> Option<HashMap<String, String>> {
+    let Some(headers) = headers else {
+        return base.clone();
+    };
+    let mut merged = base.clone().unwrap_or_default();
+    for (name, value) in headers {
+        if value.is_empty() {
+            merged.remove(&name);
+        } else {
+            merged.insert(name, value);
+        }
+    }
+    (!merged.is_empty()).then_some(merged)
+}
+
+/// Azure OpenAI 默认的 api-version
+pub const DEFAULT_AZURE_API_VERSION: &str = "2024-10-21";
+
+/// 模型接口类型
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Provider {
+    /// OpenAI 兼容的 `/chat/completions` 接口
+    #[serde(rename = "openai")]
+    OpenAI,
+    /// Azure OpenAI, base_url 为资源地址, 如 `https://<resource>.openai.azure.com`
+    Azure { deployment_id: String, api_version: String },
+    /// Anthropic Messages API, base_url 如 `https://api.anthropic.com/v1`
+    Anthropic,
+    /// Google Gemini API, base_url 如 `https://generativelanguage.googleapis.com/v1beta`
+    Gemini,
+    /// Ollama 原生 `/api/chat`, base_url 如 `http://localhost:11434`
+    Ollama,
+}
+
+impl std::fmt::Display for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Provider::OpenAI => write!(f, "openai"),
+            Provider::Azure {
+                deployment_id,
+                api_version,
+            } => write!(f, "azure(deployment={}, api-version={})", deployment_id, api_version),
+            Provider::Anthropic => write!(f, "anthropic"),
+            Provider::Gemini => write!(f, "gemini"),
+            Provider::Ollama => write!(f, "ollama"),
+        }
+    }
+}
+
+/// OpenAI 兼容接口的请求方式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum OpenAIApi {
+    /// `/chat/completions`
+    #[default]
+    ChatCompletions,
+    /// `/responses`
+    Responses,
+}
+
+impl std::fmt::Display for OpenAIApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OpenAIApi::ChatCompletions => write!(f, "chat_completions"),
+            OpenAIApi::Responses => write!(f, "responses"),
+        }
+    }
+}
+
+/// 推理模型的推理强度
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    Minimal,
+    Low,
+    Medium,
+    High,
+}
+
+/// 超出预算时的处理方式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetAction {
+    /// 拒绝发送请求
+    #[default]
+    Block,
+    /// 仅打印警告
+    Warn,
+}
+
+/// 用量预算, 按自然日/自然月统计
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BudgetConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monthly_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily_cost: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monthly_cost: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<BudgetAction>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -26,16 +177,103 @@ pub struct Config {
     pub disable_stream: bool,
     pub pure: bool,
     pub verbose: bool,
+    /// 折叠推理模型的推理过程
+    #[serde(rename = "hide-reasoning", default)]
+    pub hide_reasoning: bool,
+    /// 全局用量预算
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetConfig>,
+    /// 危险命令规则, 未设置时使用 [`default_command_rules`]
+    #[serde(rename = "command-rules", default, skip_serializing_if = "Option::is_none")]
+    pub command_rules: Option<Vec<CommandRule>>,
 }
 
 impl ModelConfig {
     /// 合并配置,优先使用self的值
     pub fn merge_with(self, base: &ModelConfig) -> Self {
         Self {
+            provider: self.provider.or_else(|| base.provider.clone()),
+            api: self.api.or(base.api),
             model_name: self.model_name.or_else(|| base.model_name.clone()),
             base_url: self.base_url.or_else(|| base.base_url.clone()),
             api_key: self.api_key.or_else(|| base.api_key.clone()),
             temperature: self.temperature.or(base.temperature),
+            max_tokens: self.max_tokens.or(base.max_tokens),
+            max_completion_tokens: self.max_completion_tokens.or(base.max_completion_tokens),
+            top_p: self.top_p.or(base.top_p),
+            frequency_penalty: self.frequency_penalty.or(base.frequency_penalty),
+            presence_penalty: self.presence_penalty.or(base.presence_penalty),
+            stop: self.stop.or_else(|| base.stop.clone()),
+            seed: self.seed.or(base.seed),
+            n: self.n.or(base.n),
+            reasoning_effort: self.reasoning_effort.or(base.reasoning_effort),
+            extra_body: self.extra_body.or_else(|| base.extra_body.clone()),
+            headers: merge_headers(self.headers, &base.headers),
+            input_price_per_mtok: self.input_price_per_mtok.or(base.input_price_per_mtok),
+            output_price_per_mtok: self.output_price_per_mtok.or(base.output_price_per_mtok),
+            budget: match (self.budget, &base.budget) {
+                (Some(budget), Some(base_budget)) => Some(budget.merge_with(base_budget)),
+                (budget, base_budget) => budget.or_else(|| base_budget.clone()),
+            },
+        }
+    }
+
+    /// 已设置的采样参数, 如 `temperature=0.3, top_p=0.9`
+    pub fn sampling_summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(val) = self.temperature {
+            parts.push(format!("temperature={}", val));
+        }
+        if let Some(val) = self.max_tokens {
+            parts.push(format!("max_tokens={}", val));
+        }
+        if let Some(val) = self.max_completion_tokens {
+            parts.push(format!("max_completion_tokens={}", val));
+        }
+        if let Some(val) = self.top_p {
+            parts.push(format!("top_p={}", val));
+        }
+        if let Some(val) = self.frequency_penalty {
+            parts.push(format!("frequency_penalty={}", val));
+        }
+        if let Some(val) = self.presence_penalty {
+            parts.push(format!("presence_penalty={}", val));
+        }
+        if let Some(val) = &self.stop {
+            parts.push(format!("stop={:?}", val));
+        }
+        if let Some(val) = self.seed {
+            parts.push(format!("seed={}", val));
+        }
+        if let Some(val) = self.n {
+            parts.push(format!("n={}", val));
+        }
+        if let Some(val) = self.reasoning_effort {
+            parts.push(format!("reasoning_effort={:?}", val).to_lowercase());
+        }
+        parts.join(", ")
+    }
+
+    /// 根据 token 用量估算费用, 未配置任何价格时返回 None
+    pub fn estimate_cost(&self, usage: &TokenUsage) -> Option<f64> {
+        if self.input_price_per_mtok.is_none() && self.output_price_per_mtok.is_none() {
+            return None;
+        }
+        let input_cost = usage.prompt_tokens as f64 * self.input_price_per_mtok.unwrap_or(0.0);
+        let output_cost = usage.completion_tokens as f64 * self.output_price_per_mtok.unwrap_or(0.0);
+        Some((input_cost + output_cost) / 1_000_000.0)
+    }
+}
+
+impl BudgetConfig {
+    /// 合并配置,优先使用self的值
+    pub fn merge_with(self, base: &BudgetConfig) -> Self {
+        Self {
+            daily_tokens: self.daily_tokens.or(base.daily_tokens),
+            monthly_tokens: self.monthly_tokens.or(base.monthly_tokens),
+            daily_cost: self.daily_cost.or(base.daily_cost),
+            monthly_cost: self.monthly_cost.or(base.monthly_cost),
+            action: self.action.or(base.action),
         }
     }
 }
@@ -53,6 +291,11 @@ impl PromptConfig {
 }
 
 impl Config {
+    /// 实际生效的危险命令规则
+    pub fn command_rules(&self) -> Vec<CommandRule> {
+        self.command_rules.clone().unwrap_or_else(default_command_rules)
+    }
+
     pub fn default() -> Self {
         Config {
             models: HashMap::new(),
@@ -62,6 +305,41 @@ impl Config {
             disable_stream: false,
             pure: false,
             verbose: false,
+            hide_reasoning: false,
+            budget: None,
+            command_rules: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_cost() {
+        let usage = TokenUsage {
+            prompt_tokens: 2_000,
+            completion_tokens: 500,
+            total_tokens: 2_500,
+        };
+
+        let model = ModelConfig::default();
+        assert_eq!(model.estimate_cost(&usage), None);
+
+        let model = ModelConfig {
+            input_price_per_mtok: Some(3.0),
+            output_price_per_mtok: Some(15.0),
+            ..Default::default()
+        };
+        let cost = model.estimate_cost(&usage).unwrap();
+        assert!((cost - 0.0135).abs() < 1e-12);
+
+        // 只配置了输出价格
+        let model = ModelConfig {
+            output_price_per_mtok: Some(2.0),
+            ..Default::default()
+        };
+        assert!((model.estimate_cost(&usage).unwrap() - 0.001).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,6 @@
+mod display;
+mod manager;
+mod structs;
+pub use display::*;
+pub use manager::*;
+pub use structs::*;
@@ -0,0 +1,82 @@
+use super::Conversation;
+use crate::chat::ChatRole;
+use crate::utils::StringUtilsTrait;
+use crossterm::style::Stylize;
+use std::io::{self, Write};
+
+/// 打印对话列表
+pub fn print_conversations(conversations: &[Conversation]) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    println!("{}", "Sessions:".on_blue().black());
+
+    // 表头
+    writeln!(
+        stdout,
+        "┌──────────────────────┬────────────────────────────────┬─────────────────────┬───────┬─────────────────────┐"
+    )?;
+    writeln!(
+        stdout,
+        "│ ID                   │ Title                          │ Model               │ Turns │ Last Updated        │"
+    )?;
+    writeln!(
+        stdout,
+        "├──────────────────────┼────────────────────────────────┼─────────────────────┼───────┼─────────────────────┤"
+    )?;
+
+    // 数据行
+    for conversation in conversations {
+        // 标题只显示第一行
+        let title = conversation.display_title();
+        writeln!(
+            stdout,
+            "│ {} │ {:<30} │ {:<19} │ {:>5} │ {} │",
+            format!("{:<20}", conversation.id).cyan(),
+            title.lines().next().unwrap_or("").safe_truncate(30),
+            conversation.model_config_name.safe_truncate(19),
+            conversation.turn_count(),
+            conversation.updated_at.format("%Y-%m-%d %H:%M:%S"),
+        )?;
+    }
+
+    // 底部
+    writeln!(
+        stdout,
+        "└──────────────────────┴────────────────────────────────┴─────────────────────┴───────┴─────────────────────┘"
+    )?;
+
+    Ok(())
+}
+
+/// 打印单个对话的全部消息
+pub fn print_conversation(conversation: &Conversation) {
+    println!(
+        "{}  {}",
+        conversation.id.as_str().cyan().bold(),
+        conversation.display_title().bold()
+    );
+    println!(
+        "{}: {}    {}: {}    {}: {}\n",
+        "model".dark_green().bold(),
+        conversation.model_config_name.as_str().blue(),
+        "prompt".dark_green().bold(),
+        conversation.prompt_config_name.as_str().blue(),
+        "created".dark_green().bold(),
+        conversation.created_at.format("%Y-%m-%d %H:%M:%S"),
+    );
+
+    for message in &conversation.messages {
+        let role = match message.message.role {
+            ChatRole::User => " user ".on_blue().black(),
+            ChatRole::Assistant => " assistant ".on_green().black(),
+        };
+        println!(
+            "{} {}",
+            role,
+            message.timestamp.format("%Y-%m-%d %H:%M:%S").to_string().dark_grey()
+        );
+        if !message.message.images.is_empty() {
+            println!("{}", format!("[{} image(s)]", message.message.images.len()).dark_grey());
+        }
+        println!("{}\n", message.message.content);
+    }
+}
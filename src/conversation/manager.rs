@@ -0,0 +1,87 @@
+use super::Conversation;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 对话文件管理器 - 每个对话保存为 conversations/<id>.json
+pub struct ConversationManager {
+    conversations_dir: PathBuf,
+}
+
+impl ConversationManager {
+    pub fn new(config_dir: &Path) -> io::Result<Self> {
+        let conversations_dir = config_dir.join("conversations");
+        if !conversations_dir.exists() {
+            fs::create_dir_all(&conversations_dir)?;
+        }
+
+        Ok(Self { conversations_dir })
+    }
+
+    fn conversation_path(&self, id: &str) -> PathBuf {
+        self.conversations_dir.join(format!("{}.json", id))
+    }
+
+    /// 保存对话到文件
+    pub fn save(&self, conversation: &Conversation) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(conversation)?;
+        fs::write(self.conversation_path(&conversation.id), content)
+    }
+
+    /// 根据id或id前缀加载对话
+    pub fn load(&self, id: &str) -> io::Result<Conversation> {
+        let path = self.conversation_path(id);
+        if path.exists() {
+            let content = fs::read_to_string(path)?;
+            return serde_json::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+        }
+
+        let mut matched: Vec<Conversation> = self.load_all()?.into_iter().filter(|c| c.id.starts_with(id)).collect();
+        match matched.len() {
+            0 => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Conversation '{}' not found", id),
+            )),
+            1 => Ok(matched.remove(0)),
+            n => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Conversation id '{}' is ambiguous ({} matches)", id, n),
+            )),
+        }
+    }
+
+    /// 删除对话文件
+    pub fn delete(&self, id: &str) -> io::Result<()> {
+        fs::remove_file(self.conversation_path(id))
+    }
+
+    /// 加载所有对话, 无法解析的文件会被跳过
+    pub fn load_all(&self) -> io::Result<Vec<Conversation>> {
+        let mut conversations = Vec::new();
+        for entry in fs::read_dir(&self.conversations_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(&path) else {
+                continue;
+            };
+            if let Ok(conversation) = serde_json::from_str::<Conversation>(&content) {
+                conversations.push(conversation);
+            }
+        }
+        Ok(conversations)
+    }
+
+    /// 按更新时间倒序列出所有对话
+    pub fn list(&self) -> io::Result<Vec<Conversation>> {
+        let mut conversations = self.load_all()?;
+        conversations.sort_by_key(|c| std::cmp::Reverse(c.updated_at));
+        Ok(conversations)
+    }
+
+    /// 最近更新的对话
+    pub fn latest(&self) -> io::Result<Option<Conversation>> {
+        Ok(self.load_all()?.into_iter().max_by_key(|c| c.updated_at))
+    }
+}
@@ -0,0 +1,84 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::chat::{ChatMessage, ChatRole, TokenUsage};
+use crate::utils::StringUtilsTrait;
+
+/// 保存在对话文件中的一条消息
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConversationMessage {
+    #[serde(flatten)]
+    pub message: ChatMessage,
+    pub timestamp: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+}
+
+/// 一次对话(多轮消息及其使用的配置)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Conversation {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(rename = "model-config")]
+    pub model_config_name: String,
+    #[serde(rename = "prompt-config")]
+    pub prompt_config_name: String,
+    #[serde(rename = "created-at")]
+    pub created_at: DateTime<Local>,
+    #[serde(rename = "updated-at")]
+    pub updated_at: DateTime<Local>,
+    pub messages: Vec<ConversationMessage>,
+}
+
+impl Conversation {
+    pub fn new(model_config_name: &str, prompt_config_name: &str) -> Self {
+        let now = Local::now();
+        Self {
+            id: format!("{}-{:04x}", now.format("%Y%m%d-%H%M%S"), rand::random::<u16>()),
+            title: None,
+            model_config_name: model_config_name.to_string(),
+            prompt_config_name: prompt_config_name.to_string(),
+            created_at: now,
+            updated_at: now,
+            messages: Vec::new(),
+        }
+    }
+
+    /// 追加一条消息并刷新更新时间
+    pub fn push(&mut self, message: ChatMessage, usage: Option<TokenUsage>) {
+        let now = Local::now();
+        self.messages.push(ConversationMessage {
+            message,
+            timestamp: now,
+            usage,
+        });
+        self.updated_at = now;
+    }
+
+    /// 标题, 未命名时取第一条用户消息的首行
+    pub fn display_title(&self) -> String {
+        if let Some(title) = &self.title {
+            return title.clone();
+        }
+        self.messages
+            .iter()
+            .find(|m| m.message.role == ChatRole::User)
+            .and_then(|m| m.message.content.lines().next())
+            .map(|line| line.safe_substring(50).to_string())
+            .unwrap_or_default()
+    }
+
+    /// 对话轮数(用户消息数)
+    pub fn turn_count(&self) -> usize {
+        self.messages
+            .iter()
+            .filter(|m| m.message.role == ChatRole::User)
+            .count()
+    }
+
+    /// 用于发送请求的消息历史
+    pub fn chat_messages(&self) -> Vec<ChatMessage> {
+        self.messages.iter().map(|m| m.message.clone()).collect()
+    }
+}
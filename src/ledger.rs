@@ -0,0 +1,10 @@
+mod budget;
+mod display;
+mod manager;
+mod stats;
+mod structs;
+pub use budget::*;
+pub use display::*;
+pub use manager::*;
+pub use stats::*;
+pub use structs::*;
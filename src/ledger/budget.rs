@@ -0,0 +1,153 @@
+use super::LedgerEntry;
+use crate::config::BudgetConfig;
+use crate::utils::format_cost;
+use chrono::{DateTime, Datelike, Local};
+use std::fmt;
+
+/// 预算超限时的退出码 (EX_TEMPFAIL)
+pub const BUDGET_EXCEEDED_EXIT_CODE: i32 = 75;
+
+/// 一项超出的预算
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetViolation {
+    /// 预算范围, 如 global / model 'gpt'
+    pub scope: String,
+    /// daily / monthly
+    pub period: &'static str,
+    /// 已用量及上限, 已格式化
+    pub used: String,
+    pub limit: String,
+}
+
+impl fmt::Display for BudgetViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} budget exceeded: {} / {}",
+            self.scope, self.period, self.used, self.limit
+        )
+    }
+}
+
+/// 预算超限, 拒绝发送请求
+#[derive(Debug)]
+pub struct BudgetExceeded {
+    pub violations: Vec<BudgetViolation>,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<String> = self.violations.iter().map(|v| v.to_string()).collect();
+        write!(f, "{} (use --ignore-budget to override)", messages.join("; "))
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+/// 根据账本记录检查预算, `model_filter` 为 None 时统计所有模型
+pub fn check_budget(
+    entries: &[LedgerEntry],
+    now: DateTime<Local>,
+    scope: &str,
+    budget: &BudgetConfig,
+    model_filter: Option<&str>,
+) -> Vec<BudgetViolation> {
+    let mut daily = (0u64, 0f64);
+    let mut monthly = (0u64, 0f64);
+    for entry in entries {
+        if model_filter.is_some_and(|name| name != entry.model_config_name) {
+            continue;
+        }
+        let time = entry.timestamp;
+        if time.year() != now.year() || time.month() != now.month() {
+            continue;
+        }
+        monthly.0 += entry.total_tokens as u64;
+        monthly.1 += entry.cost.unwrap_or(0.0);
+        if time.day() == now.day() {
+            daily.0 += entry.total_tokens as u64;
+            daily.1 += entry.cost.unwrap_or(0.0);
+        }
+    }
+
+    let mut violations = Vec::new();
+    let limits = [
+        ("daily", daily, budget.daily_tokens, budget.daily_cost),
+        ("monthly", monthly, budget.monthly_tokens, budget.monthly_cost),
+    ];
+    for (period, (tokens, cost), token_limit, cost_limit) in limits {
+        if let Some(limit) = token_limit.filter(|limit| tokens >= *limit) {
+            violations.push(BudgetViolation {
+                scope: scope.to_string(),
+                period,
+                used: format!("{} tokens", tokens),
+                limit: format!("{} tokens", limit),
+            });
+        }
+        if let Some(limit) = cost_limit.filter(|limit| cost >= *limit) {
+            violations.push(BudgetViolation {
+                scope: scope.to_string(),
+                period,
+                used: format_cost(cost),
+                limit: format_cost(limit),
+            });
+        }
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(month: u32, day: u32, model: &str, tokens: u32, cost: f64) -> LedgerEntry {
+        LedgerEntry {
+            timestamp: Local.with_ymd_and_hms(2026, month, day, 12, 0, 0).unwrap(),
+            model_config_name: model.to_string(),
+            model_name: model.to_string(),
+            prompt_tokens: tokens,
+            completion_tokens: 0,
+            total_tokens: tokens,
+            cost: Some(cost),
+            latency_ms: 0,
+            success: true,
+        }
+    }
+
+    #[test]
+    fn test_check_budget() {
+        let now = Local.with_ymd_and_hms(2026, 10, 17, 18, 0, 0).unwrap();
+        let entries = vec![
+            entry(10, 17, "a", 600, 0.5),
+            entry(10, 17, "b", 600, 0.5),
+            entry(10, 1, "a", 5_000, 2.0),
+            entry(9, 30, "a", 100_000, 50.0),
+        ];
+
+        let budget = BudgetConfig {
+            daily_tokens: Some(1_000),
+            monthly_cost: Some(3.5),
+            ..Default::default()
+        };
+        let violations = check_budget(&entries, now, "global", &budget, None);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].period, "daily");
+        assert_eq!(
+            violations[0].to_string(),
+            "global daily budget exceeded: 1200 tokens / 1000 tokens"
+        );
+
+        // 只统计模型 a
+        let violations = check_budget(&entries, now, "model 'a'", &budget, Some("a"));
+        assert!(violations.is_empty());
+
+        let budget = BudgetConfig {
+            monthly_tokens: Some(5_600),
+            ..Default::default()
+        };
+        let violations = check_budget(&entries, now, "model 'a'", &budget, Some("a"));
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].period, "monthly");
+    }
+}
@@ -0,0 +1,57 @@
+use super::{StatsReport, StatsRow};
+use crate::utils::{StringUtilsTrait, format_cost};
+use crossterm::style::Stylize;
+use std::io::{self, Write};
+
+/// 打印用量统计
+pub fn print_stats(report: &StatsReport) -> io::Result<()> {
+    println!("{}", "Usage by period:".on_blue().black());
+    print_stats_table(&report.by_period, "Period")?;
+    println!();
+    println!("{}", "Usage by model:".on_blue().black());
+    print_stats_table(&report.by_model, "Model")?;
+    Ok(())
+}
+
+fn print_stats_table(rows: &[StatsRow], key_title: &str) -> io::Result<()> {
+    let mut stdout = io::stdout();
+
+    // 表头
+    writeln!(
+        stdout,
+        "┌───────────────────────────────┬──────────┬────────┬────────────┬────────────┬────────────┬────────────┬─────────────┐"
+    )?;
+    writeln!(
+        stdout,
+        "│ {:<29} │ Requests │ Failed │     Prompt │ Completion │      Total │       Cost │ Avg Latency │",
+        key_title
+    )?;
+    writeln!(
+        stdout,
+        "├───────────────────────────────┼──────────┼────────┼────────────┼────────────┼────────────┼────────────┼─────────────┤"
+    )?;
+
+    // 数据行
+    for row in rows {
+        writeln!(
+            stdout,
+            "│ {:<29} │ {:>8} │ {:>6} │ {:>10} │ {:>10} │ {:>10} │ {:>10} │ {:>9}ms │",
+            row.key.safe_truncate(29),
+            row.requests,
+            row.failures,
+            row.prompt_tokens,
+            row.completion_tokens,
+            row.total_tokens,
+            format_cost(row.cost),
+            row.avg_latency_ms,
+        )?;
+    }
+
+    // 底部
+    writeln!(
+        stdout,
+        "└───────────────────────────────┴──────────┴────────┴────────────┴────────────┴────────────┴────────────┴─────────────┘"
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use super::LedgerEntry;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// 用量账本管理器 - 只追加写入 usage.jsonl, 每行一条记录
+pub struct LedgerManager {
+    ledger_path: PathBuf,
+}
+
+impl LedgerManager {
+    pub fn new(config_dir: &Path) -> io::Result<Self> {
+        if !config_dir.exists() {
+            fs::create_dir_all(config_dir)?;
+        }
+
+        Ok(Self {
+            ledger_path: config_dir.join("usage.jsonl"),
+        })
+    }
+
+    /// 追加一条记录
+    pub fn append(&self, entry: &LedgerEntry) -> io::Result<()> {
+        let line = serde_json::to_string(entry)?;
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.ledger_path)?;
+        writeln!(file, "{}", line)
+    }
+
+    /// 加载所有记录, 无法解析的行会被跳过
+    pub fn load(&self) -> io::Result<Vec<LedgerEntry>> {
+        if !self.ledger_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.ledger_path)?;
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
@@ -0,0 +1,133 @@
+use super::LedgerEntry;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 统计周期
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StatsPeriod {
+    Day,
+    Week,
+    Month,
+}
+
+impl StatsPeriod {
+    /// 记录所属周期的标签, 如 2026-10-17 / 2026-W42 / 2026-10
+    pub fn label(&self, entry: &LedgerEntry) -> String {
+        match self {
+            StatsPeriod::Day => entry.timestamp.format("%Y-%m-%d").to_string(),
+            StatsPeriod::Week => entry.timestamp.format("%G-W%V").to_string(),
+            StatsPeriod::Month => entry.timestamp.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+/// 一组记录的汇总
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct StatsRow {
+    pub key: String,
+    pub requests: u64,
+    pub failures: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    pub cost: f64,
+    pub avg_latency_ms: u64,
+}
+
+/// 按周期和按模型的汇总报告
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsReport {
+    pub by_period: Vec<StatsRow>,
+    pub by_model: Vec<StatsRow>,
+}
+
+impl StatsReport {
+    pub fn new(entries: &[LedgerEntry], period: StatsPeriod) -> Self {
+        let by_period = aggregate(entries, |e| period.label(e));
+        let mut by_model = aggregate(entries, |e| format!("{} ({})", e.model_config_name, e.model_name));
+        by_model.sort_by(|a, b| b.total_tokens.cmp(&a.total_tokens).then(a.key.cmp(&b.key)));
+        Self { by_period, by_model }
+    }
+}
+
+/// 按 key 分组汇总, 结果按 key 升序
+fn aggregate(entries: &[LedgerEntry], key_of: impl Fn(&LedgerEntry) -> String) -> Vec<StatsRow> {
+    let mut groups: BTreeMap<String, (StatsRow, u64)> = BTreeMap::new();
+    for entry in entries {
+        let key = key_of(entry);
+        let (row, latency_sum) = groups.entry(key.clone()).or_insert_with(|| {
+            (
+                StatsRow {
+                    key,
+                    ..Default::default()
+                },
+                0,
+            )
+        });
+        row.requests += 1;
+        if !entry.success {
+            row.failures += 1;
+        }
+        row.prompt_tokens += entry.prompt_tokens as u64;
+        row.completion_tokens += entry.completion_tokens as u64;
+        row.total_tokens += entry.total_tokens as u64;
+        row.cost += entry.cost.unwrap_or(0.0);
+        *latency_sum += entry.latency_ms;
+    }
+
+    groups
+        .into_values()
+        .map(|(mut row, latency_sum)| {
+            row.avg_latency_ms = latency_sum / row.requests;
+            row
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    fn entry(day: u32, model: &str, tokens: u32, cost: Option<f64>, success: bool) -> LedgerEntry {
+        LedgerEntry {
+            timestamp: Local.with_ymd_and_hms(2026, 10, day, 12, 0, 0).unwrap(),
+            model_config_name: model.to_string(),
+            model_name: format!("{}-model", model),
+            prompt_tokens: tokens,
+            completion_tokens: tokens,
+            total_tokens: tokens * 2,
+            cost,
+            latency_ms: 100 * day as u64,
+            success,
+        }
+    }
+
+    #[test]
+    fn test_stats_report() {
+        let entries = vec![
+            entry(5, "a", 10, Some(0.5), true),
+            entry(5, "b", 100, None, false),
+            entry(6, "a", 20, Some(0.25), true),
+        ];
+
+        let report = StatsReport::new(&entries, StatsPeriod::Day);
+        assert_eq!(report.by_period.len(), 2);
+        assert_eq!(report.by_period[0].key, "2026-10-05");
+        assert_eq!(report.by_period[0].requests, 2);
+        assert_eq!(report.by_period[0].failures, 1);
+        assert_eq!(report.by_period[0].total_tokens, 220);
+        assert_eq!(report.by_period[0].avg_latency_ms, 500);
+
+        // 按模型汇总, 用量多的在前
+        assert_eq!(report.by_model[0].key, "b (b-model)");
+        assert_eq!(report.by_model[1].key, "a (a-model)");
+        assert_eq!(report.by_model[1].requests, 2);
+        assert!((report.by_model[1].cost - 0.75).abs() < 1e-12);
+
+        let report = StatsReport::new(&entries, StatsPeriod::Month);
+        assert_eq!(report.by_period.len(), 1);
+        assert_eq!(report.by_period[0].key, "2026-10");
+    }
+}
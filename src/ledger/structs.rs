@@ -0,0 +1,56 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// 用量账本中的一条记录(每个完成的请求一条), 字段名与配置文件一样使用 kebab-case
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct LedgerEntry {
+    pub timestamp: DateTime<Local>,
+    #[serde(rename = "model-config")]
+    pub model_config_name: String,
+    #[serde(rename = "model-name")]
+    pub model_name: String,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub total_tokens: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    pub latency_ms: u64,
+    pub success: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_field_names() {
+        let entry = LedgerEntry {
+            timestamp: Local::now(),
+            model_config_name: "mock".to_string(),
+            model_name: "gpt-4o".to_string(),
+            prompt_tokens: 10,
+            completion_tokens: 5,
+            total_tokens: 15,
+            cost: None,
+            latency_ms: 120,
+            success: true,
+        };
+        let value = serde_json::to_value(&entry).unwrap();
+        let mut keys: Vec<&str> = value.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                "completion-tokens",
+                "latency-ms",
+                "model-config",
+                "model-name",
+                "prompt-tokens",
+                "success",
+                "timestamp",
+                "total-tokens"
+            ]
+        );
+    }
+}
@@ -2,9 +2,14 @@ use tokio::runtime::Runtime;
 
 use crate::utils::logger::init_logger;
 
+mod attachment;
 mod config;
 mod cli;
 mod chat;
+mod conversation;
+mod ledger;
+mod schema;
+mod shell;
 mod utils;
 
 fn main()->Result<(),Box<dyn std::error::Error>>{
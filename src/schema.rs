@@ -0,0 +1,2 @@
+mod json_schema;
+pub use json_schema::*;
@@ -0,0 +1,155 @@
+use crate::chat::ResponseFormat;
+use jsonschema::Validator;
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// 回复不符合 Schema 时的退出码(EX_DATAERR)
+pub const SCHEMA_MISMATCH_EXIT_CODE: i32 = 65;
+
+/// 校验失败时最多列出的错误数
+const MAX_REPORTED_ERRORS: usize = 5;
+
+/// 通过 `--schema` 指定的 JSON Schema
+pub struct JsonSchema {
+    /// 传给接口的 Schema 名称, 取 `title` 或文件名
+    pub name: String,
+    pub schema: Value,
+    validator: Validator,
+}
+
+/// 回复无法解析或不符合 Schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaMismatch(pub String);
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for SchemaMismatch {}
+
+impl JsonSchema {
+    /// 读取并编译 Schema 文件
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let schema: Value = serde_json::from_str(&content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("invalid JSON: {}", e)))?;
+        let validator = jsonschema::validator_for(&schema)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("invalid JSON Schema: {}", e)))?;
+        let title = schema.get("title").and_then(Value::as_str);
+        let stem = path.file_stem().map(|stem| stem.to_string_lossy());
+        let name = schema_name(title.or(stem.as_deref()).unwrap_or_default());
+        Ok(JsonSchema {
+            name,
+            schema,
+            validator,
+        })
+    }
+
+    /// 请求接口按 Schema 输出
+    pub fn response_format(&self) -> ResponseFormat {
+        ResponseFormat::JsonSchema {
+            name: self.name.clone(),
+            schema: self.schema.clone(),
+        }
+    }
+
+    /// 附加到 system prompt 的说明, 接口不支持 JSON Schema 时也能约束输出
+    pub fn instruction(&self) -> String {
+        format!(
+            "Respond only with a single JSON value that conforms to the following JSON Schema. \
+             Do not wrap it in a code block or add any explanation.\n\n{}",
+            serde_json::to_string_pretty(&self.schema).unwrap_or_default()
+        )
+    }
+
+    /// 解析回复并按 Schema 校验, 兼容模型用 ```json 代码块包裹的回复
+    pub fn parse_reply(&self, reply: &str) -> Result<Value, SchemaMismatch> {
+        let value: Value = serde_json::from_str(strip_code_fence(reply))
+            .map_err(|e| SchemaMismatch(format!("reply is not valid JSON: {}", e)))?;
+        let errors: Vec<String> = self
+            .validator
+            .iter_errors(&value)
+            .take(MAX_REPORTED_ERRORS)
+            .map(|error| {
+                let path = error.instance_path().to_string();
+                format!("{}: {}", if path.is_empty() { "/" } else { &path }, error)
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(value)
+        } else {
+            Err(SchemaMismatch(format!(
+                "reply does not match the schema:\n{}",
+                errors.join("\n")
+            )))
+        }
+    }
+}
+
+/// 接口要求名称只包含字母、数字、`_` 和 `-`, 且不超过 64 个字符
+fn schema_name(raw: &str) -> String {
+    let name: String = raw
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .take(64)
+        .collect();
+    if name.trim_matches('_').is_empty() {
+        "response".to_string()
+    } else {
+        name
+    }
+}
+
+fn strip_code_fence(reply: &str) -> &str {
+    let reply = reply.trim();
+    let Some(body) = reply.strip_prefix("```") else {
+        return reply;
+    };
+    // 去掉语言标记所在的首行
+    let body = body.split_once('\n').map_or("", |(_, rest)| rest);
+    body.trim_end().strip_suffix("```").unwrap_or(body).trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn person_schema() -> JsonSchema {
+        let schema = json!({
+            "type": "object",
+            "properties": { "name": { "type": "string" }, "age": { "type": "integer" } },
+            "required": ["name", "age"]
+        });
+        JsonSchema {
+            name: "person".to_string(),
+            validator: jsonschema::validator_for(&schema).unwrap(),
+            schema,
+        }
+    }
+
+    #[test]
+    fn test_parse_reply() {
+        let schema = person_schema();
+        assert_eq!(
+            schema.parse_reply("```json\n{\"name\": \"a\", \"age\": 3}\n```"),
+            Ok(json!({"name": "a", "age": 3}))
+        );
+        let error = schema.parse_reply("{\"name\": \"a\", \"age\": \"3\"}").unwrap_err();
+        assert!(error.0.contains("/age"), "{}", error);
+        assert!(schema.parse_reply("Sure! Here it is").is_err());
+    }
+
+    #[test]
+    fn test_schema_name() {
+        assert_eq!(schema_name("Person Info"), "Person_Info");
+        assert_eq!(schema_name("todo-list"), "todo-list");
+        assert_eq!(schema_name("人员"), "response");
+        assert_eq!(schema_name(&"x".repeat(80)).len(), 64);
+    }
+}
@@ -0,0 +1,16 @@
+mod capture;
+mod confirm;
+mod danger;
+mod env;
+mod exec;
+mod history;
+mod init;
+mod prompt;
+pub use capture::*;
+pub use confirm::*;
+pub use danger::*;
+pub use env::*;
+pub use exec::*;
+pub use history::*;
+pub use init::*;
+pub use prompt::*;
@@ -0,0 +1,126 @@
+use super::MAX_OUTPUT_BYTES;
+use super::exec::{exit_code, tail_text};
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// 命令的执行结果, 输出只保留末尾 [`MAX_OUTPUT_BYTES`]
+#[derive(Debug)]
+pub struct CapturedRun {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: i32,
+}
+
+/// 直接执行命令(不经过 shell), 输出实时显示的同时被捕获
+///
+/// `input` 不为空时写入命令的 stdin, 否则继承当前的 stdin。
+pub fn run_and_capture(command: &[String], input: Option<&str>) -> io::Result<CapturedRun> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty command"))?;
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::inherit()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // 单独的线程写入 stdin, 避免命令输出填满管道时互相等待
+    let writer = child.stdin.take().zip(input).map(|(mut stdin, input)| {
+        let input = input.to_string();
+        thread::spawn(move || {
+            let _ = stdin.write_all(input.as_bytes());
+        })
+    });
+    let stdout = child
+        .stdout
+        .take()
+        .map(|out| thread::spawn(move || tee(out, io::stdout())));
+    let stderr = child
+        .stderr
+        .take()
+        .map(|err| thread::spawn(move || tee(err, io::stderr())));
+
+    let status = child.wait()?;
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+    let collect = |handle: Option<thread::JoinHandle<io::Result<TailBuffer>>>| -> io::Result<String> {
+        match handle {
+            Some(handle) => Ok(handle
+                .join()
+                .map_err(|_| io::Error::other("output thread panicked"))??
+                .into_string()),
+            None => Ok(String::new()),
+        }
+    };
+    Ok(CapturedRun {
+        stdout: collect(stdout)?,
+        stderr: collect(stderr)?,
+        exit_code: exit_code(status),
+    })
+}
+
+/// 将输出原样写到终端, 同时保留末尾部分
+fn tee(mut reader: impl Read, mut writer: impl Write) -> io::Result<TailBuffer> {
+    let mut buffer = TailBuffer::default();
+    let mut chunk = [0u8; 8192];
+    loop {
+        let n = match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        writer.write_all(&chunk[..n])?;
+        writer.flush()?;
+        buffer.push(&chunk[..n]);
+    }
+    Ok(buffer)
+}
+
+/// 只保留末尾 [`MAX_OUTPUT_BYTES`] 的缓冲区, 避免输出很多的命令占用大量内存
+#[derive(Debug, Default)]
+struct TailBuffer {
+    data: Vec<u8>,
+    dropped: usize,
+}
+
+impl TailBuffer {
+    fn push(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+        // 超过两倍上限时才丢弃, 减少移动数据的次数
+        if self.data.len() > 2 * MAX_OUTPUT_BYTES {
+            let excess = self.data.len() - MAX_OUTPUT_BYTES;
+            self.data.drain(..excess);
+            self.dropped += excess;
+        }
+    }
+
+    fn into_string(self) -> String {
+        tail_text(&self.data, self.dropped, MAX_OUTPUT_BYTES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail_buffer() {
+        let mut buffer = TailBuffer::default();
+        for _ in 0..5 {
+            buffer.push(&[b'a'; MAX_OUTPUT_BYTES]);
+        }
+        buffer.push(b"the end");
+        let text = buffer.into_string();
+        assert!(text.starts_with(&format!("[... {} bytes truncated ...]\n", 4 * MAX_OUTPUT_BYTES + 7)));
+        assert!(text.ends_with("aaathe end"));
+        assert_eq!(text.len() - text.find('\n').unwrap() - 1, MAX_OUTPUT_BYTES);
+    }
+}
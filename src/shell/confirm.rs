@@ -0,0 +1,140 @@
+use crate::config::{CommandRisk, CommandRule};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use crossterm::style::Stylize;
+use std::fs;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// 生成命令后用户的选择
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandAction {
+    Execute,
+    Edit,
+    Copy,
+    Abort,
+}
+
+impl CommandAction {
+    /// 解析用户输入, `E` 执行、`e` 编辑需区分大小写
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim() {
+            "E" => Some(CommandAction::Execute),
+            "e" => Some(CommandAction::Edit),
+            "c" | "C" => Some(CommandAction::Copy),
+            "a" | "A" => Some(CommandAction::Abort),
+            _ => None,
+        }
+    }
+}
+
+/// 高亮打印命令
+pub fn print_command(command: &str) {
+    println!("{} {}", "$".dark_grey(), command.cyan().bold());
+}
+
+/// 询问如何处理命令, 读到 EOF(Ctrl+D) 时视为放弃
+pub fn ask_action() -> io::Result<CommandAction> {
+    loop {
+        print!("{} ", "[E]xecute / [e]dit / [c]opy / [a]bort:".dark_green());
+        io::stdout().flush()?;
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line)? == 0 {
+            println!();
+            return Ok(CommandAction::Abort);
+        }
+        match CommandAction::parse(&line) {
+            Some(action) => return Ok(action),
+            None => eprintln!("{}", "⚠ Please enter E, e, c or a.".yellow()),
+        }
+    }
+}
+
+/// 提示命令命中的危险规则
+pub fn print_risk(rule: &CommandRule) {
+    match rule.risk {
+        CommandRisk::Deny => eprintln!(
+            "{}",
+            format!("❌Dangerous command ({}), it will not be executed.", rule.description).red()
+        ),
+        CommandRisk::Confirm => eprintln!("{}", format!("⚠ Dangerous command: {}.", rule.description).yellow()),
+    }
+}
+
+/// 执行危险命令前要求输入 `yes`
+pub fn confirm_dangerous() -> io::Result<bool> {
+    print!("{} ", "Type 'yes' to execute this command:".yellow());
+    io::stdout().flush()?;
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+    Ok(line.trim() == "yes")
+}
+
+/// 用 `$VISUAL`/`$EDITOR` 编辑命令, 编辑结果为空时保留原命令
+pub fn edit_command(command: &str) -> io::Result<String> {
+    let path = std::env::temp_dir().join(format!("aichat-cmd-{}.sh", std::process::id()));
+    fs::write(&path, format!("{}\n", command))?;
+
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+    // 编辑器可能带参数, 如 `code --wait`
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or_default();
+    let status = Command::new(program).args(parts).arg(&path).status();
+    let edited = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+
+    let status = status?;
+    if !status.success() {
+        return Err(io::Error::other(format!("editor '{}' exited with {}", editor, status)));
+    }
+    let edited = edited?.trim().to_string();
+    Ok(if edited.is_empty() { command.to_string() } else { edited })
+}
+
+/// 复制到剪贴板, 依次尝试系统剪贴板工具, 都不可用时使用终端的 OSC 52 序列
+pub fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    const TOOLS: &[(&str, &[&str])] = &[
+        ("pbcopy", &[]),
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard"]),
+        ("xsel", &["--clipboard", "--input"]),
+        ("clip.exe", &[]),
+    ];
+    for (program, args) in TOOLS {
+        let Ok(mut child) = Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if child.wait()?.success() {
+            return Ok(());
+        }
+    }
+    print!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+    io::stdout().flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_action() {
+        assert_eq!(CommandAction::parse("E\n"), Some(CommandAction::Execute));
+        assert_eq!(CommandAction::parse("e"), Some(CommandAction::Edit));
+        assert_eq!(CommandAction::parse(" c "), Some(CommandAction::Copy));
+        assert_eq!(CommandAction::parse("a"), Some(CommandAction::Abort));
+        assert_eq!(CommandAction::parse(""), None);
+        assert_eq!(CommandAction::parse("execute"), None);
+    }
+}
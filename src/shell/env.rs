@@ -0,0 +1,38 @@
+use std::path::Path;
+
+/// 当前运行环境的操作系统和 shell
+#[derive(Debug, Clone)]
+pub struct ShellEnv {
+    /// 操作系统, 如 `linux`、`macos`、`windows`
+    pub os: &'static str,
+    /// shell 可执行文件, 来自 `$SHELL`
+    pub shell: String,
+}
+
+impl ShellEnv {
+    /// 检测当前环境, 未设置 `$SHELL` 时 Windows 使用 PowerShell, 其他系统使用 `/bin/sh`
+    pub fn detect() -> Self {
+        let shell = std::env::var("SHELL")
+            .ok()
+            .filter(|shell| !shell.trim().is_empty())
+            .unwrap_or_else(|| {
+                if cfg!(windows) {
+                    "powershell".to_string()
+                } else {
+                    "/bin/sh".to_string()
+                }
+            });
+        Self {
+            os: std::env::consts::OS,
+            shell,
+        }
+    }
+
+    /// shell 名称, 如 `zsh`、`bash`、`powershell`
+    pub fn shell_name(&self) -> &str {
+        Path::new(&self.shell)
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .unwrap_or(&self.shell)
+    }
+}
@@ -0,0 +1,79 @@
+use super::ShellEnv;
+use std::io;
+use std::process::{Command, ExitStatus, Stdio};
+
+/// 在用户的 shell 中执行命令, 继承当前终端的输入输出, 返回退出码
+pub fn run_in_shell(env: &ShellEnv, command: &str) -> io::Result<i32> {
+    let status = shell_command(env, command).status()?;
+    Ok(exit_code(status))
+}
+
+/// 发送给模型的命令输出上限
+pub const MAX_OUTPUT_BYTES: usize = 8 * 1024;
+
+/// 在用户的 shell 中执行命令并捕获输出, stdout 和 stderr 依次拼接, 返回输出和退出码
+pub fn capture_output(env: &ShellEnv, command: &str) -> io::Result<(String, i32)> {
+    let output = shell_command(env, command).stdin(Stdio::null()).output()?;
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    Ok((text, exit_code(output.status)))
+}
+
+/// 超过 `max_bytes` 时只保留末尾部分, 错误信息通常在输出的最后
+pub fn tail_truncate(text: &str, max_bytes: usize) -> String {
+    tail_text(text.as_bytes(), 0, max_bytes)
+}
+
+/// 取 `bytes` 末尾不超过 `max_bytes` 的部分, `dropped` 为此前已丢弃的字节数
+pub(super) fn tail_text(bytes: &[u8], dropped: usize, max_bytes: usize) -> String {
+    let mut start = bytes.len().saturating_sub(max_bytes);
+    // 不在 UTF-8 字符中间截断
+    while start > 0 && start < bytes.len() && bytes[start] & 0xC0 == 0x80 {
+        start += 1;
+    }
+    let text = String::from_utf8_lossy(&bytes[start..]);
+    match dropped + start {
+        0 => text.into_owned(),
+        truncated => format!("[... {} bytes truncated ...]\n{}", truncated, text),
+    }
+}
+
+/// 构造通过 shell 执行命令的 [`Command`]
+pub fn shell_command(env: &ShellEnv, command: &str) -> Command {
+    let flag = match env.shell_name().to_lowercase().as_str() {
+        "powershell" | "pwsh" => "-Command",
+        "cmd" => "/C",
+        _ => "-c",
+    };
+    let mut cmd = Command::new(&env.shell);
+    cmd.arg(flag).arg(command);
+    cmd
+}
+
+/// 被信号终止时按 shell 的惯例返回 128 + 信号值
+pub(super) fn exit_code(status: ExitStatus) -> i32 {
+    if let Some(code) = status.code() {
+        return code;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail_truncate() {
+        assert_eq!(tail_truncate("short", 10), "short");
+        assert_eq!(tail_truncate("abcdef", 3), "[... 3 bytes truncated ...]\ndef");
+        // 不在字符边界处截断
+        assert_eq!(tail_truncate("错误x", 2), "[... 6 bytes truncated ...]\nx");
+    }
+}
@@ -0,0 +1,142 @@
+use super::ShellEnv;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// shell 历史文件, 优先使用 `$HISTFILE`, 否则按 shell 使用默认位置
+pub fn history_file(env: &ShellEnv) -> Option<PathBuf> {
+    if let Some(file) = std::env::var_os("HISTFILE").filter(|file| !file.is_empty()) {
+        return Some(PathBuf::from(file));
+    }
+    let home = dirs::home_dir()?;
+    match env.shell_name() {
+        "zsh" => Some(home.join(".zsh_history")),
+        "fish" => {
+            // fish 在所有平台上都使用 XDG 目录
+            let data_dir = std::env::var_os("XDG_DATA_HOME")
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+                .unwrap_or_else(|| home.join(".local/share"));
+            Some(data_dir.join("fish/fish_history"))
+        }
+        _ => Some(home.join(".bash_history")),
+    }
+}
+
+/// 读取历史中最后一条命令, 跳过 `aichat fix` 自身
+pub fn last_command(env: &ShellEnv) -> io::Result<Option<String>> {
+    let Some(path) = history_file(env) else {
+        return Ok(None);
+    };
+    // zsh 的历史文件可能包含非 UTF-8 字节
+    let content = String::from_utf8_lossy(&fs::read(path)?).into_owned();
+    let commands = parse_history(&content, env.shell_name() == "fish");
+    Ok(commands.iter().rev().find_map(|command| strip_fix_invocation(command)))
+}
+
+/// 解析历史文件, 支持 bash(含 `#时间戳` 行)、zsh 扩展格式(`: 时间戳:耗时;命令`)和 fish 格式
+pub fn parse_history(content: &str, fish: bool) -> Vec<String> {
+    if fish {
+        return content
+            .lines()
+            .filter_map(|line| line.strip_prefix("- cmd: "))
+            .map(unescape_fish)
+            .collect();
+    }
+    let mut commands = Vec::new();
+    let mut pending: Option<String> = None;
+    for line in content.lines() {
+        let line = match pending.take() {
+            // zsh 多行命令以 `\` 结尾续行
+            Some(mut command) => {
+                command.push('\n');
+                command.push_str(line);
+                command
+            }
+            None => {
+                if line.len() > 1 && line.starts_with('#') && line[1..].chars().all(|c| c.is_ascii_digit()) {
+                    continue;
+                }
+                match line.strip_prefix(": ").and_then(|rest| rest.split_once(';')) {
+                    Some((meta, command)) if meta.contains(':') => command.to_string(),
+                    _ => line.to_string(),
+                }
+            }
+        };
+        if let Some(command) = line.strip_suffix('\\') {
+            pending = Some(command.to_string());
+        } else if !line.trim().is_empty() {
+            commands.push(line);
+        }
+    }
+    commands.extend(pending);
+    commands
+}
+
+/// fish 历史中 `\n` 表示换行, `\\` 表示反斜杠
+fn unescape_fish(command: &str) -> String {
+    let mut result = String::with_capacity(command.len());
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                result.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                result.push('\\');
+                chars.next();
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// 跳过 `aichat fix` 命令; `make 2>&1 | aichat fix` 这类管道取管道前的命令
+fn strip_fix_invocation(command: &str) -> Option<String> {
+    let is_fix = |part: &str| {
+        let mut words = part.split_whitespace();
+        words.next().is_some_and(|program| program.ends_with("aichat")) && words.next() == Some("fix")
+    };
+    let command = command.trim();
+    if is_fix(command) {
+        return None;
+    }
+    match command.rsplit_once('|') {
+        Some((before, after)) if is_fix(after) => {
+            let before = before.trim();
+            (!before.is_empty()).then(|| before.to_string())
+        }
+        _ => Some(command.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_history() {
+        let bash = "ls\n#1700000000\ncargo build\n\n";
+        assert_eq!(parse_history(bash, false), vec!["ls", "cargo build"]);
+        let zsh = ": 1700000000:0;git status\n: 1700000001:2;for f in *; do\\\necho $f\\\ndone\n";
+        assert_eq!(
+            parse_history(zsh, false),
+            vec!["git status", "for f in *; do\necho $f\ndone"]
+        );
+        let fish =
+            "- cmd: echo a\\\\b\n  when: 1700000000\n- cmd: printf 'x\\ny'\n  when: 1700000001\n  paths:\n    - x\n";
+        assert_eq!(parse_history(fish, true), vec!["echo a\\b", "printf 'x\ny'"]);
+    }
+
+    #[test]
+    fn test_strip_fix_invocation() {
+        assert_eq!(strip_fix_invocation("aichat fix --rerun"), None);
+        assert_eq!(
+            strip_fix_invocation("make 2>&1 | aichat fix"),
+            Some("make 2>&1".to_string())
+        );
+        assert_eq!(strip_fix_invocation("ls | grep fix"), Some("ls | grep fix".to_string()));
+    }
+}
@@ -0,0 +1,16 @@
+/// 支持按键集成的 shell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ShellKind {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// `aichat shell-init` 输出的集成脚本, 快捷键把命令行内容交给 `aichat cmd --print` 并替换为建议的命令
+pub fn init_script(shell: ShellKind) -> &'static str {
+    match shell {
+        ShellKind::Bash => include_str!("scripts/aichat.bash"),
+        ShellKind::Zsh => include_str!("scripts/aichat.zsh"),
+        ShellKind::Fish => include_str!("scripts/aichat.fish"),
+    }
+}
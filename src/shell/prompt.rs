@@ -0,0 +1,147 @@
+use super::{CapturedRun, ShellEnv};
+
+/// `aichat cmd` 使用的提示配置名, 只用于显示和记录
+pub const CMD_PROMPT_NAME: &str = "cmd";
+
+/// `aichat fix` 使用的提示配置名, 只用于显示和记录
+pub const FIX_PROMPT_NAME: &str = "fix";
+
+/// 生成单条 shell 命令的系统提示
+pub fn command_prompt(env: &ShellEnv) -> String {
+    format!(
+        r#"You are a shell command generator running on {os} with the {shell} shell.
+Reply with exactly one {shell} command that accomplishes the user's request on {os}.
+Output only the command itself: no explanation, no markdown, no code fences, no leading `$`.
+If several steps are needed, chain them on a single line with pipes, `&&` or `;`.
+"#,
+        os = env.os,
+        shell = env.shell_name()
+    )
+}
+
+/// 解释失败原因并给出修正命令的系统提示
+pub fn fix_prompt(env: &ShellEnv) -> String {
+    format!(
+        r#"You are a shell expert running on {os} with the {shell} shell.
+The user ran a command that failed. Briefly explain why it failed, in a few sentences.
+Then give exactly one corrected {shell} command in a fenced code block, as the last thing in your reply.
+If the command cannot be fixed, explain why and do not give a code block.
+"#,
+        os = env.os,
+        shell = env.shell_name()
+    )
+}
+
+/// `aichat fix` 的用户消息, 包含失败的命令以及已知的输出和退出码
+pub fn fix_message(command: &str, output: Option<&str>, exit_code: Option<i32>) -> String {
+    let mut message = format!("Command:\n```\n{}\n```\n", command);
+    if let Some(code) = exit_code {
+        message.push_str(&format!("Exit status: {}\n", code));
+    }
+    match output.map(str::trim_end).filter(|output| !output.is_empty()) {
+        Some(output) => message.push_str(&format!("Output:\n```\n{}\n```\n", output)),
+        None => message.push_str("The output is not available.\n"),
+    }
+    message
+}
+
+/// `aichat run` 的用户消息, 包含命令、退出码、stdout、stderr 和问题
+pub fn run_message(command: &[String], run: &CapturedRun, question: Option<&str>) -> String {
+    let question = question.unwrap_or(if run.exit_code == 0 {
+        "Explain what this output means."
+    } else {
+        "Explain why this command failed and how to fix it."
+    });
+    let section = |name: &str, output: &str| match output.trim_end() {
+        "" => format!("{}: (empty)\n", name),
+        output => format!("{}:\n```\n{}\n```\n", name, output),
+    };
+    format!(
+        "I ran this command:\n```\n{}\n```\nExit status: {}\n{}{}\n{}",
+        quote_command(command),
+        run.exit_code,
+        section("Stdout", &run.stdout),
+        section("Stderr", &run.stderr),
+        question
+    )
+}
+
+/// 按 POSIX shell 的规则拼接参数, 含特殊字符的参数用单引号包裹
+pub fn quote_command(command: &[String]) -> String {
+    command
+        .iter()
+        .map(|arg| {
+            let plain = !arg.is_empty()
+                && arg
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+            if plain {
+                arg.clone()
+            } else {
+                format!("'{}'", arg.replace('\'', "'\\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 提取回复中最后一个代码块作为修正后的命令
+pub fn extract_last_code_block(reply: &str) -> Option<String> {
+    let (before, _) = reply.trim_end().rsplit_once("```")?;
+    let (_, block) = before.rsplit_once("```")?;
+    // 跳过围栏后的语言标记, 如 ```bash
+    let command = block.split_once('\n').map_or("", |(_, rest)| rest).trim();
+    (!command.is_empty()).then(|| command.to_string())
+}
+
+/// 从模型回复中提取命令, 去掉模型仍可能添加的代码块围栏、反引号和 `$ ` 前缀
+pub fn extract_command(reply: &str) -> String {
+    let mut command = reply.trim();
+    if let Some(fenced) = command.strip_prefix("```") {
+        // 跳过围栏后的语言标记, 如 ```bash
+        command = fenced.split_once('\n').map_or("", |(_, rest)| rest);
+        command = command.trim_end().strip_suffix("```").unwrap_or(command);
+    }
+    let mut command = command.trim();
+    if command.len() >= 2 && command.starts_with('`') && command.ends_with('`') {
+        command = command[1..command.len() - 1].trim();
+    }
+    command.strip_prefix("$ ").unwrap_or(command).trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_command() {
+        assert_eq!(extract_command("  ls -la\n"), "ls -la");
+        assert_eq!(extract_command("`du -sh *`"), "du -sh *");
+        assert_eq!(extract_command("$ df -h"), "df -h");
+        assert_eq!(
+            extract_command("```bash\nfind . -mtime 0 -size +100M\n```"),
+            "find . -mtime 0 -size +100M"
+        );
+        assert_eq!(
+            extract_command("```\nfor f in *; do echo $f; done\n```\n"),
+            "for f in *; do echo $f; done"
+        );
+    }
+
+    #[test]
+    fn test_extract_last_code_block() {
+        let reply = "`gti` is a typo, use `git`:\n\n```bash\ngit status\n```\n";
+        assert_eq!(extract_last_code_block(reply), Some("git status".to_string()));
+        assert_eq!(
+            extract_last_code_block("```\nls\n```\nthen\n```sh\nls -la\n```"),
+            Some("ls -la".to_string())
+        );
+        assert_eq!(extract_last_code_block("It cannot be fixed."), None);
+    }
+
+    #[test]
+    fn test_quote_command() {
+        let command = ["grep", "-r", "hello world", "it's", ""].map(String::from);
+        assert_eq!(quote_command(&command), r#"grep -r 'hello world' 'it'\''s' ''"#);
+    }
+}
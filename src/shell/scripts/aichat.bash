@@ -0,0 +1,13 @@
+# aichat shell integration for bash, add to ~/.bashrc:
+#   eval "$(aichat shell-init bash)"
+# Ctrl+G replaces the current command line with a suggested command, without running it.
+_aichat_widget() {
+    [[ -z "$READLINE_LINE" ]] && return
+    local suggestion
+    suggestion=$(aichat cmd --print -- "$READLINE_LINE" </dev/null) || return
+    if [[ -n "$suggestion" ]]; then
+        READLINE_LINE=$suggestion
+        READLINE_POINT=${#READLINE_LINE}
+    fi
+}
+bind -x '"\C-g": _aichat_widget'
@@ -0,0 +1,14 @@
+# aichat shell integration for fish, add to ~/.config/fish/config.fish:
+#   aichat shell-init fish | source
+# Ctrl+G replaces the current command line with a suggested command, without running it.
+function _aichat_widget
+    set -l buffer (commandline)
+    test -z "$buffer"; and return
+    set -l suggestion (aichat cmd --print -- "$buffer" </dev/null | string collect)
+    if test -n "$suggestion"
+        commandline -r -- $suggestion
+        commandline -C (string length -- $suggestion)
+    end
+    commandline -f repaint
+end
+bind \cg _aichat_widget
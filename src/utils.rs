@@ -1,5 +1,7 @@
 pub mod logger;
 
+mod json;
 mod string;
+pub use json::*;
 pub use string::*;
-// pub use logger::*;
\ No newline at end of file
+// pub use logger::*;
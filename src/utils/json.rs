@@ -0,0 +1,66 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// 序列化为 [`Value`], 经过文本中转以保留 f32 的精度
+///
+/// `serde_json::to_value` 会把 f32 扩展为 f64, `0.9` 变成 `0.8999999761581421`。
+pub fn to_json_value<T: Serialize>(value: &T) -> serde_json::Result<Value> {
+    serde_json::from_str(&serde_json::to_string(value)?)
+}
+
+/// 按 JSON Merge Patch (RFC 7386) 的规则把 `patch` 深度合并到 `target`
+///
+/// 对象逐键递归合并, `null` 表示删除该键, 其他值直接覆盖。
+pub fn merge_json(target: &mut Value, patch: &Value) {
+    let Value::Object(patch_map) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Default::default());
+    }
+    let target_map = target.as_object_mut().unwrap();
+    for (key, value) in patch_map {
+        if value.is_null() {
+            target_map.remove(key);
+        } else {
+            merge_json(target_map.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_to_json_value_keeps_f32() {
+        assert_eq!(to_json_value(&0.9_f32).unwrap(), json!(0.9));
+    }
+
+    #[test]
+    fn test_merge_json() {
+        let mut target = json!({
+            "model": "a",
+            "stream_options": {"include_usage": true},
+            "provider": {"order": ["x"], "allow_fallbacks": true}
+        });
+        merge_json(
+            &mut target,
+            &json!({
+                "top_k": 20,
+                "stream_options": null,
+                "provider": {"order": ["y", "z"]}
+            }),
+        );
+        assert_eq!(
+            target,
+            json!({
+                "model": "a",
+                "top_k": 20,
+                "provider": {"order": ["y", "z"], "allow_fallbacks": true}
+            })
+        );
+    }
+}
@@ -126,6 +126,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::unnecessary_literal_unwrap)]
     fn test_logger() {
         init_logger(); // 从环境变量初始化
         // 或者手动设置: set_log_level(LogLevel::Debug);
@@ -4,6 +4,9 @@ pub trait StringUtilsTrait {
     ///
     /// 如果不足 `max_chars`，则返回完整字符串。
     fn safe_substring(&self, max_chars: usize) -> &str;
+
+    /// 截断到最多 `max_chars` 个字符, 超出时以 `...` 结尾, 用于表格列
+    fn safe_truncate(&self, max_chars: usize) -> String;
 }
 
 impl StringUtilsTrait for str {
@@ -17,24 +20,36 @@ impl StringUtilsTrait for str {
         }
         &self[..end]
     }
+
+    fn safe_truncate(&self, max_chars: usize) -> String {
+        if self.chars().count() > max_chars {
+            format!("{}...", self.safe_substring(max_chars.saturating_sub(3)))
+        } else {
+            self.to_string()
+        }
+    }
 }
 
 pub struct StringUtils;
 impl StringUtils {
-    pub fn mask_sensitive(str: &String) -> String {
-        let len = str.len();
+    pub fn mask_sensitive(str: &str) -> String {
+        // 按字符计算, 避免切在多字节字符中间
+        let chars: Vec<char> = str.chars().collect();
+        let len = chars.len();
+        let head = |n: usize| chars[..n].iter().collect::<String>();
+        let tail = |n: usize| chars[len - n..].iter().collect::<String>();
 
         match len {
             0 => String::new(),
             1 => String::from("*"),
-            1..=4 => format!("{}{}", &str[0..1], "*".repeat(len - 1)), // 长度0-4直接返回原字符串
+            2..=4 => format!("{}{}", head(1), "*".repeat(len - 1)), // 长度0-4直接返回原字符串
             5..=10 => {
                 // 长度5-10，保留首尾各2个字符
-                format!("{}****{}", &str[0..2], &str[len - 2..])
+                format!("{}****{}", head(2), tail(2))
             }
             _ => {
                 // 长度10+，保留首尾各4个字符
-                format!("{}****{}", &str[0..4], &str[len - 4..])
+                format!("{}****{}", head(4), tail(4))
             }
         }
     }
@@ -44,4 +59,68 @@ impl StringUtilsTrait for String {
     fn safe_substring(&self, max_chars: usize) -> &str {
         self.as_str().safe_substring(max_chars)
     }
+
+    fn safe_truncate(&self, max_chars: usize) -> String {
+        self.as_str().safe_truncate(max_chars)
+    }
+}
+
+/// 读取环境变量, 用作 [`expand_env_vars`] 等函数的 `env` 参数
+pub fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+/// 展开字符串中的 `${VAR}` 环境变量引用, 未定义的变量展开为空字符串
+///
+/// 变量通过 `env` 查找, 一般传入 [`env_var`]
+pub fn expand_env_vars(input: &str, env: impl Fn(&str) -> Option<String>) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        result.push_str(&rest[..start]);
+        let name = &rest[start + 2..start + 2 + len];
+        result.push_str(&env(name).unwrap_or_default());
+        rest = &rest[start + 3 + len..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// 格式化费用, 小额费用保留更多小数位
+pub fn format_cost(cost: f64) -> String {
+    if cost < 0.01 {
+        format!("${:.6}", cost)
+    } else {
+        format!("${:.4}", cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_sensitive() {
+        assert_eq!(StringUtils::mask_sensitive("sk-1234567890"), "sk-1****7890");
+        assert_eq!(StringUtils::mask_sensitive("我的应用"), "我***");
+        assert_eq!(StringUtils::mask_sensitive("终端助手测试应用名称"), "终端****名称");
+    }
+
+    #[test]
+    fn test_safe_truncate() {
+        assert_eq!("gpt-4o".safe_truncate(10), "gpt-4o");
+        assert_eq!("abcdefghij".safe_truncate(8), "abcde...");
+        assert_eq!("你好世界你好".safe_truncate(5), "你好...");
+    }
+
+    #[test]
+    fn test_expand_env_vars() {
+        let env = |name: &str| (name == "TEAM").then(|| "42".to_string());
+        assert_eq!(expand_env_vars("team-${TEAM}", env), "team-42");
+        assert_eq!(expand_env_vars("${UNDEFINED}x", env), "x");
+        assert_eq!(expand_env_vars("plain $HOME ${open", env), "plain $HOME ${open");
+    }
 }